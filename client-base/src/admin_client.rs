@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use log::{info, debug};
+use xml::reader::{XmlEvent as XmlReadEvent, EventReader};
+use xml::writer::{EmitterConfig, EventWriter};
+use crate::protocol::{Authenticate, Observe, Observed, Pause, Prepare, Prepared, Step};
+use crate::transport::Transport;
+use crate::util::SCResult;
+use crate::xml_node::{XmlNode, FromXmlNode};
+
+/// A client for the administrative side of the protocol,
+/// which lets scripts and tests prepare, observe and
+/// control games on a server.
+pub struct SCAdminClient {
+    xml_reader: EventReader<Box<dyn Read + Send>>,
+    xml_writer: EventWriter<Box<dyn Write + Send>>,
+    pending: VecDeque<XmlNode>
+}
+
+impl SCAdminClient {
+    /// Connects to the given address via TCP
+    /// and authenticates using the password.
+    pub fn connect(host: &str, port: u16, password: &str) -> SCResult<Self> {
+        let address = format!("{}:{}", host, port);
+        let stream = TcpStream::connect(&address)?;
+        info!("Connected to {}", address);
+
+        Self::connect_with(stream, password)
+    }
+
+    /// Authenticates over the provided transport
+    /// using the password.
+    pub fn connect_with<T>(transport: T, password: &str) -> SCResult<Self> where T: Transport, T::Reader: Send + 'static, T::Writer: 'static {
+        let (reader, mut writer) = transport.split()?;
+        writer.write_all("<protocol>".as_bytes())?;
+
+        let mut emitter_config = EmitterConfig::new();
+        emitter_config.write_document_declaration = false;
+
+        let reader: Box<dyn Read + Send> = Box::new(reader);
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+        let mut client = Self {
+            xml_reader: EventReader::new(reader),
+            xml_writer: emitter_config.create_writer(writer),
+            pending: VecDeque::new()
+        };
+
+        info!("Authenticating as administrator");
+        client.send(Authenticate { password: password.to_owned() }.into())?;
+
+        // Read initial protocol element
+        info!("Waiting for initial <protocol>...");
+        loop {
+            match client.xml_reader.next()? {
+                XmlReadEvent::StartElement { name, .. } if name.local_name == "protocol" => break,
+                XmlReadEvent::EndDocument => return Err("Reached end of document while waiting for <protocol>".into()),
+                _ => ()
+            }
+        }
+
+        Ok(client)
+    }
+
+    /// Prepares a room and blocks until the
+    /// reservation codes have been received.
+    pub fn prepare(&mut self, prepare: Prepare) -> SCResult<Prepared> {
+        self.send(prepare.into())?;
+        Prepared::from_node(&self.receive_response("prepared")?)
+    }
+
+    /// Starts observing a room, after which its
+    /// messages can be fetched using `receive`.
+    pub fn observe(&mut self, room_id: &str) -> SCResult<Observed> {
+        self.send(Observe { room_id: room_id.to_owned() }.into())?;
+        Observed::from_node(&self.receive_response("observed")?)
+    }
+
+    /// Pauses or resumes the game in a room.
+    pub fn pause(&mut self, room_id: &str, pause: bool) -> SCResult<()> {
+        self.send(Pause { room_id: room_id.to_owned(), pause }.into())
+    }
+
+    /// Lets a paused game advance by a single move.
+    pub fn step(&mut self, room_id: &str) -> SCResult<()> {
+        self.send(Step { room_id: room_id.to_owned() }.into())
+    }
+
+    /// Blocks until the next message arrives that is
+    /// not a response, e.g. from an observed room.
+    pub fn receive(&mut self) -> SCResult<XmlNode> {
+        match self.pending.pop_front() {
+            Some(node) => Ok(node),
+            None => self.read()
+        }
+    }
+
+    /// Sends a message to the server.
+    fn send(&mut self, node: XmlNode) -> SCResult<()> {
+        debug!("Sending {}", node);
+        node.write_to(&mut self.xml_writer)?;
+        self.xml_writer.inner_mut().flush()?;
+        Ok(())
+    }
+
+    /// Reads the next message from the server.
+    fn read(&mut self) -> SCResult<XmlNode> {
+        let node = XmlNode::read_from(&mut self.xml_reader)?;
+        debug!("Got XML node {}", node);
+        Ok(node)
+    }
+
+    /// Blocks until the response with the given tag name
+    /// arrives, queueing other messages in the meantime.
+    fn receive_response(&mut self, name: &str) -> SCResult<XmlNode> {
+        loop {
+            let node = self.read()?;
+            match node.name() {
+                n if n == name => return Ok(node),
+                "error" => return Err(format!("Got error from server: {}", node.attribute("message")?).into()),
+                "close" | "sc.protocol.responses.CloseConnection" => return Err("The server closed the connection".into()),
+                _ => self.pending.push_back(node)
+            }
+        }
+    }
+}
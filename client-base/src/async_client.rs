@@ -0,0 +1,398 @@
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use log::{info, debug, warn, error};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::{task, time};
+use tokio_util::io::SyncIoBridge;
+use xml::reader::{XmlEvent as XmlReadEvent, EventReader};
+use xml::writer::EmitterConfig;
+use crate::dispatch::{self, DelegateHooks, Dispatch, Dispatcher, MoveConfig, panic_message};
+use crate::history::GameHistory;
+use crate::xml_node::XmlNode;
+use crate::util::SCResult;
+use crate::move_budget::{Expiry, MoveBudget, DEFAULT_MOVE_BUDGET};
+use crate::plugin::{SCPlugin, HasPlayerColor, HasTurn};
+use crate::ponder::PonderJob;
+use crate::protocol::GameResult;
+use crate::summary::{GameSummary, TurnStats};
+use crate::telemetry::{TelemetrySink, TelemetryWorker};
+
+/// The interval in which the client checks for published
+/// candidates once the deadline of a move has passed.
+const CANDIDATE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The asynchronous counterpart to `SCClientDelegate`,
+/// which is driven by an `AsyncSCClient`. The hooks
+/// are invoked like their synchronous counterparts.
+#[async_trait]
+pub trait AsyncSCClientDelegate: Send + 'static {
+    /// The plugin defining which types are
+    /// representing various parts of the game.
+    type Plugin: SCPlugin;
+
+    /// Invoked whenever the game state updates.
+    fn on_update_state(&mut self, _state: &<Self::Plugin as SCPlugin>::GameState) {}
+
+    /// Invoked when the game ends.
+    fn on_game_end(&mut self, _result: GameResult<Self::Plugin>) {}
+
+    /// Invoked before the client joins a new game.
+    fn on_new_game(&mut self) {}
+
+    /// Invoked when the welcome message is received
+    /// with the player's color.
+    fn on_welcome_message(&mut self, _color: &<Self::Plugin as SCPlugin>::PlayerColor) {}
+
+    /// Invoked when the client has joined a room.
+    fn on_joined(&mut self, _room_id: &str) {}
+
+    /// Invoked when the client has left a room.
+    fn on_left(&mut self, _room_id: &str) {}
+
+    /// Invoked when the server reports an error.
+    fn on_server_error(&mut self, _message: &str) {}
+
+    /// Invoked when the server closes the connection.
+    fn on_close(&mut self) {}
+
+    /// Invoked with messages the client could not handle.
+    fn on_unrecognized_message(&mut self, _node: &XmlNode) {}
+
+    /// Invoked with the opponent's inferred move.
+    fn on_opponent_move(&mut self, _game_move: &<Self::Plugin as SCPlugin>::Move) {}
+
+    /// Invoked after a move has been sent to the server.
+    fn on_move_sent(&mut self, _game_move: &<Self::Plugin as SCPlugin>::Move) {}
+
+    /// Invoked after a move has been sent to collect
+    /// metrics about its computation for telemetry.
+    fn move_metrics(&mut self) -> Vec<(String, f64)> { Vec::new() }
+
+    /// Invoked after a state update during the opponent's turn.
+    /// The returned job runs on a background thread (not on the
+    /// runtime) and is cancelled once the next state arrives.
+    fn ponder(&mut self, _state: &<Self::Plugin as SCPlugin>::GameState, _my_color: <Self::Plugin as SCPlugin>::PlayerColor) -> Option<PonderJob> { None }
+
+    /// Requests a move from the delegate. Like its synchronous
+    /// counterpart, it may publish candidates to the budget, the
+    /// latest of which is sent once the deadline is reached.
+    async fn request_move(&mut self, state: &<Self::Plugin as SCPlugin>::GameState, my_color: <Self::Plugin as SCPlugin>::PlayerColor, history: &GameHistory<Self::Plugin>, budget: &MoveBudget<<Self::Plugin as SCPlugin>::Move>) -> <Self::Plugin as SCPlugin>::Move;
+}
+
+/// Adapts an asynchronous delegate to the hooks
+/// invoked by the shared message dispatch.
+struct AsyncHooks<D>(D);
+
+impl<D> DelegateHooks for AsyncHooks<D> where D: AsyncSCClientDelegate {
+    type Plugin = D::Plugin;
+
+    fn on_update_state(&mut self, state: &<Self::Plugin as SCPlugin>::GameState) { self.0.on_update_state(state) }
+    fn on_game_end(&mut self, result: GameResult<Self::Plugin>) { self.0.on_game_end(result) }
+    fn on_new_game(&mut self) { self.0.on_new_game() }
+    fn on_welcome_message(&mut self, color: &<Self::Plugin as SCPlugin>::PlayerColor) { self.0.on_welcome_message(color) }
+    fn on_joined(&mut self, room_id: &str) { self.0.on_joined(room_id) }
+    fn on_left(&mut self, room_id: &str) { self.0.on_left(room_id) }
+    fn on_server_error(&mut self, message: &str) { self.0.on_server_error(message) }
+    fn on_close(&mut self) { self.0.on_close() }
+    fn on_unrecognized_message(&mut self, node: &XmlNode) { self.0.on_unrecognized_message(node) }
+    fn on_opponent_move(&mut self, game_move: &<Self::Plugin as SCPlugin>::Move) { self.0.on_opponent_move(game_move) }
+    fn on_move_sent(&mut self, game_move: &<Self::Plugin as SCPlugin>::Move) { self.0.on_move_sent(game_move) }
+    fn move_metrics(&mut self) -> Vec<(String, f64)> { self.0.move_metrics() }
+    fn ponder(&mut self, state: &<Self::Plugin as SCPlugin>::GameState, my_color: <Self::Plugin as SCPlugin>::PlayerColor) -> Option<PonderJob> { self.0.ponder(state, my_color) }
+}
+
+/// A client which handles XML requests asynchronously,
+/// thus allowing many games to run concurrently
+/// in a single process. Messages are dispatched like
+/// in `SCClient`, but debug modes, recordings and
+/// custom transports are only supported there.
+pub struct AsyncSCClient<D> where D: AsyncSCClientDelegate {
+    dispatcher: Dispatcher<AsyncHooks<D>>,
+    move_config: MoveConfig,
+    telemetry: Option<Box<dyn TelemetrySink<D::Plugin>>>,
+    game_count: usize
+}
+
+/// Catches panics while polling the wrapped future,
+/// which isolates panicking delegates.
+struct CatchUnwind<F>(F);
+
+impl<D> AsyncSCClient<D> where D: AsyncSCClientDelegate {
+    /// Creates a new client using the specified delegate.
+    pub fn new(delegate: D) -> Self {
+        Self {
+            dispatcher: Dispatcher::new(AsyncHooks(delegate)),
+            move_config: MoveConfig { budget: DEFAULT_MOVE_BUDGET, guard: false, watchdog: None },
+            telemetry: None,
+            game_count: 1
+        }
+    }
+
+    /// Sets the time the delegate may spend on a single move.
+    pub fn with_move_budget(mut self, move_budget: Duration) -> Self {
+        self.move_config.budget = move_budget;
+        self
+    }
+
+    /// Enables replacing illegal moves by the
+    /// first legal one before they are sent.
+    pub fn with_move_guard(mut self, move_guard: bool) -> Self {
+        self.move_config.guard = move_guard;
+        self
+    }
+
+    /// Enables a watchdog sending a fallback move if the
+    /// delegate has neither returned a move nor published
+    /// a candidate within the given time. Unlike in the
+    /// synchronous client, the delegate's computation is
+    /// cancelled afterwards.
+    pub fn with_watchdog(mut self, timeout: Duration) -> Self {
+        self.move_config.watchdog = Some(timeout);
+        self
+    }
+
+    /// Sets the sink recording telemetry about each turn.
+    pub fn with_telemetry(mut self, sink: impl TelemetrySink<D::Plugin> + 'static) -> Self {
+        self.telemetry = Some(Box::new(sink));
+        self
+    }
+
+    /// Sets the factory creating the delegates for rooms
+    /// joined after the first one, which is played by
+    /// the delegate passed to the constructor.
+    pub fn with_delegate_factory(mut self, mut delegate_factory: impl FnMut() -> D + Send + 'static) -> Self {
+        self.dispatcher.set_delegate_factory(Box::new(move || AsyncHooks(delegate_factory())));
+        self
+    }
+
+    /// Sets the number of games `run` plays back-to-back.
+    pub fn with_game_count(mut self, game_count: usize) -> Self {
+        self.game_count = game_count;
+        self
+    }
+
+    /// Connects to the given address via TCP and handles game
+    /// messages until the server closes the connection, which
+    /// is repeated for each game. Returns a summary of each
+    /// played game.
+    pub async fn run(mut self, host: &str, port: u16, reservation: Option<&str>) -> SCResult<Vec<GameSummary<D::Plugin>>> {
+        let mut summaries = Vec::new();
+
+        for game in 0..self.game_count {
+            let join = dispatch::join_message::<D::Plugin>(reservation.filter(|_| game == 0));
+            summaries.extend(self.run_joining(host, port, &[join]).await?);
+        }
+
+        Ok(summaries)
+    }
+
+    /// Connects to the given address via TCP and plays the
+    /// prepared games with the given reservation codes on
+    /// a single connection. Requires a delegate factory.
+    pub async fn run_multiple(mut self, host: &str, port: u16, reservations: &[&str]) -> SCResult<Vec<GameSummary<D::Plugin>>> {
+        let joins: Vec<_> = reservations.iter().map(|&res| dispatch::join_message::<D::Plugin>(Some(res))).collect();
+        self.run_joining(host, port, &joins).await
+    }
+
+    /// Connects to the given address, sends the join
+    /// messages and handles the game messages.
+    async fn run_joining(&mut self, host: &str, port: u16, joins: &[XmlNode]) -> SCResult<Vec<GameSummary<D::Plugin>>> {
+        let address = format!("{}:{}", host, port);
+        let stream = TcpStream::connect(&address).await?;
+        info!("Connected to {}", address);
+
+        let (reader, mut writer) = stream.into_split();
+        writer.write_all("<protocol>".as_bytes()).await?;
+
+        for join in joins {
+            info!("Sending join message {}", join);
+            writer.write_all(join.to_string().as_bytes()).await?;
+        }
+
+        self.dispatcher.begin_game();
+        let telemetry = self.telemetry.take().map(TelemetryWorker::start);
+        let result = self.run_game(reader, writer, telemetry.as_ref()).await;
+        let summaries = self.dispatcher.end_connection();
+
+        // Waiting for the sink blocks, which is why
+        // it happens outside of the runtime
+        if let Some(telemetry) = telemetry {
+            self.telemetry = task::spawn_blocking(move || telemetry.finish()).await.ok().flatten();
+        }
+
+        result.map(|()| summaries)
+    }
+
+    /// Parses/handles game messages from the provided reader.
+    async fn run_game<R, W>(&mut self, reader: R, mut writer: W, telemetry: Option<&TelemetryWorker<D::Plugin>>) -> SCResult<()> where R: AsyncRead + Unpin + Send + 'static, W: AsyncWrite + Unpin + Send {
+        let mut nodes = Self::read_nodes(reader);
+
+        while let Some(node) = nodes.recv().await {
+            let node = node?;
+            debug!("Got XML node {}", node);
+
+            match self.dispatcher.dispatch(&node) {
+                Dispatch::MoveRequest(room_id) => self.request_move(&room_id, &mut writer, telemetry).await?,
+                Dispatch::Close => break,
+                Dispatch::Continue => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Requests a move from the room's delegate and sends it,
+    /// or the latest candidate once the deadline has passed.
+    async fn request_move<W>(&mut self, room_id: &str, writer: &mut W, telemetry: Option<&TelemetryWorker<D::Plugin>>) -> SCResult<()> where W: AsyncWrite + Unpin {
+        let config = self.move_config;
+        let session = match self.dispatcher.session(room_id) {
+            Some(session) => session,
+            None => return Ok(())
+        };
+        let state = match session.history.current_state() {
+            Some(state) => state,
+            None => {
+                error!("Got move request, which cannot be fulfilled since no game state is present!");
+                return Ok(());
+            }
+        };
+        let turn = state.turn();
+        let color = state.player_color();
+        info!("Got move request in room {} @ turn: {}, color: {:?}", room_id, turn, color);
+
+        let started = Instant::now();
+        let budget = MoveBudget::new(started + config.budget);
+        let hard_deadline = config.watchdog.map(|timeout| started + timeout);
+        let stats = |timed_out, fallback| TurnStats { turn, duration: started.elapsed(), timed_out, fallback };
+        let mut sent = None;
+
+        let mut request = CatchUnwind(session.delegate.0.request_move(state, color, &session.history, &budget));
+        let new_move = loop {
+            let now = Instant::now();
+            let wake_up = if now < budget.deadline() { budget.deadline() } else { now + CANDIDATE_POLL_INTERVAL };
+            match time::timeout_at(wake_up.into(), &mut request).await {
+                Ok(new_move) => break Some(new_move),
+                Err(_) => match budget.poll_expiry(hard_deadline) {
+                    Some(Expiry::Candidate(candidate)) => {
+                        info!("Reached deadline, sending latest candidate");
+                        let candidate = Self::send_move(writer, room_id, state, config, candidate).await?;
+                        sent = Some((candidate, stats(true, false)));
+                    },
+                    Some(Expiry::HardDeadline) => {
+                        error!("Delegate has not provided a move in time, sending fallback\nState: {:?}", state);
+                        if let Some(fallback) = dispatch::fallback_move::<D::Plugin>(state) {
+                            let fallback = Self::send_move(writer, room_id, state, config, fallback).await?;
+                            sent = Some((fallback, stats(true, true)));
+                        }
+                        break None;
+                    },
+                    _ => {}
+                }
+            }
+        };
+
+        match new_move {
+            Some(Ok(new_move)) => match budget.finish(new_move) {
+                Some(new_move) => {
+                    let new_move = Self::send_move(writer, room_id, state, config, new_move).await?;
+                    sent = Some((new_move, stats(false, false)));
+                },
+                None => warn!("Discarding move returned after the deadline, a candidate has already been sent")
+            },
+            Some(Err(payload)) => {
+                error!("Delegate panicked while computing a move in room {}: {}\nState: {:?}", room_id, panic_message(&payload), state);
+                let fallback = match budget.fail() {
+                    Some(Some(candidate)) => Some(candidate),
+                    Some(None) => dispatch::fallback_move::<D::Plugin>(state),
+                    None => None
+                };
+                if let Some(fallback) = fallback {
+                    let fallback = Self::send_move(writer, room_id, state, config, fallback).await?;
+                    sent = Some((fallback, stats(false, true)));
+                }
+            },
+            None => {}
+        }
+        drop(request);
+
+        if let Some((sent_move, stats)) = sent {
+            session.complete_turn(room_id, sent_move, stats, telemetry);
+        }
+        Ok(())
+    }
+
+    /// Parses XML nodes from the reader on a blocking
+    /// thread, since the XML parser is synchronous.
+    fn read_nodes<R>(reader: R) -> mpsc::UnboundedReceiver<SCResult<XmlNode>> where R: AsyncRead + Unpin + Send + 'static {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let bridge = SyncIoBridge::new(reader);
+
+        task::spawn_blocking(move || {
+            let mut xml_reader = EventReader::new(bridge);
+
+            // Read initial protocol element
+            info!("Waiting for initial <protocol>...");
+            loop {
+                match xml_reader.next() {
+                    Ok(XmlReadEvent::StartElement { name, .. }) if name.local_name == "protocol" => break,
+                    Ok(XmlReadEvent::EndDocument) => {
+                        let _ = sender.send(Err("Reached end of document while waiting for <protocol>".into()));
+                        return;
+                    },
+                    Err(e) => {
+                        let _ = sender.send(Err(e.into()));
+                        return;
+                    },
+                    _ => ()
+                }
+            }
+
+            loop {
+                let node = XmlNode::read_from(&mut xml_reader);
+                let failed = node.is_err();
+                if sender.send(node).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        receiver
+    }
+
+    /// Sends a move to the given room, replacing it by
+    /// a legal one first if the guard is enabled.
+    async fn send_move<W>(writer: &mut W, room_id: &str, state: &<D::Plugin as SCPlugin>::GameState, config: MoveConfig, game_move: <D::Plugin as SCPlugin>::Move) -> SCResult<<D::Plugin as SCPlugin>::Move> where W: AsyncWrite + Unpin {
+        let game_move = if config.guard { dispatch::legal_move::<D::Plugin>(state, game_move) } else { game_move };
+        let move_node = dispatch::move_message::<D::Plugin>(room_id, game_move.clone())?;
+        debug!("Sending move {}", move_node);
+
+        let mut emitter_config = EmitterConfig::new();
+        emitter_config.write_document_declaration = false;
+        let mut xml_writer = emitter_config.create_writer(Vec::new());
+        move_node.write_to(&mut xml_writer)?;
+
+        writer.write_all(&xml_writer.into_inner()).await?;
+        writer.flush().await?;
+        Ok(game_move)
+    }
+}
+
+impl<F> Future for CatchUnwind<F> where F: Future + Unpin {
+    type Output = thread::Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = &mut self.0;
+        match panic::catch_unwind(AssertUnwindSafe(|| Pin::new(inner).poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(payload))
+        }
+    }
+}
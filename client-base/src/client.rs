@@ -1,18 +1,32 @@
-use std::convert::TryFrom;
 use std::net::TcpStream;
-use std::io::{self, BufWriter, BufReader, Read, Write};
+use std::io::{self, Cursor, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
 use log::{info, debug, warn, error};
 use xml::reader::{XmlEvent as XmlReadEvent, EventReader};
-use xml::writer::EmitterConfig;
-use crate::xml_node::{XmlNode, FromXmlNode};
+use xml::writer::{EmitterConfig, EventWriter};
+use crate::dispatch::{self, DelegateHooks, Dispatch, Dispatcher, MoveConfig, RoomSession, panic_message};
+use crate::history::GameHistory;
+use crate::move_budget::{Expiry, MoveBudget, DEFAULT_MOVE_BUDGET};
+use crate::ponder::PonderJob;
+use crate::recording::{Direction, Recorder, read_recording};
+use crate::transport::Transport;
+use crate::xml_node::XmlNode;
 use crate::util::SCResult;
 use crate::plugin::{SCPlugin, HasPlayerColor, HasTurn};
-use crate::protocol::{Joined, Left, Room, Data, GameResult};
+use crate::protocol::GameResult;
+use crate::summary::{GameSummary, TurnStats};
+use crate::telemetry::{TelemetrySink, TelemetryWorker};
 
 /// A handler that implements the game player's
 /// behavior, usually employing some custom move
-/// selection strategy.
-pub trait SCClientDelegate {
+/// selection strategy. Moves are requested on a
+/// background thread, which is why delegates
+/// have to be `Send`.
+pub trait SCClientDelegate: Send + 'static {
     /// The plugin defining which types are
     /// representing various parts of the game.
     type Plugin: SCPlugin;
@@ -23,163 +37,553 @@ pub trait SCClientDelegate {
     /// Invoked when the game ends.
     fn on_game_end(&mut self, _result: GameResult<Self::Plugin>) {}
     
+    /// Invoked before the client joins a new game, which
+    /// lets delegates that play several consecutive games
+    /// reset their per-game caches.
+    fn on_new_game(&mut self) {}
+    
     /// Invoked when the welcome message is received
     /// with the player's color.
     fn on_welcome_message(&mut self, _color: &<Self::Plugin as SCPlugin>::PlayerColor) {}
     
+    /// Invoked when the client has joined a room.
+    fn on_joined(&mut self, _room_id: &str) {}
+    
+    /// Invoked when the client has left a room.
+    fn on_left(&mut self, _room_id: &str) {}
+    
+    /// Invoked when the server reports an error,
+    /// e.g. because a move has been rejected.
+    fn on_server_error(&mut self, _message: &str) {}
+    
+    /// Invoked when the server closes the connection,
+    /// right before `run` returns.
+    fn on_close(&mut self) {}
+    
+    /// Invoked with messages the client could not handle.
+    fn on_unrecognized_message(&mut self, _node: &XmlNode) {}
+    
+    /// Invoked with the opponent's move, which is inferred
+    /// from the states before and after it.
+    fn on_opponent_move(&mut self, _game_move: &<Self::Plugin as SCPlugin>::Move) {}
+    
+    /// Invoked after a move has been sent to the server.
+    fn on_move_sent(&mut self, _game_move: &<Self::Plugin as SCPlugin>::Move) {}
+    
+    /// Invoked after a move has been sent to collect metrics
+    /// about its computation for telemetry, e.g. the number
+    /// of searched nodes or the move's evaluation.
+    fn move_metrics(&mut self) -> Vec<(String, f64)> { Vec::new() }
+    
+    /// Invoked after a state update during the opponent's turn.
+    /// The returned job runs on a background thread and is
+    /// cancelled once the next state arrives.
+    fn ponder(&mut self, _state: &<Self::Plugin as SCPlugin>::GameState, _my_color: <Self::Plugin as SCPlugin>::PlayerColor) -> Option<PonderJob> { None }
+    
     /// Requests a move from the delegate. This method
-    /// should implement the "main" game logic.
-    fn request_move(&mut self, state: &<Self::Plugin as SCPlugin>::GameState, my_color: <Self::Plugin as SCPlugin>::PlayerColor) -> <Self::Plugin as SCPlugin>::Move;
+    /// should implement the "main" game logic. The history
+    /// holds the game's previous states and moves. Long-running
+    /// searches can publish candidates to the budget, the
+    /// latest of which is sent once the deadline is reached.
+    fn request_move(&mut self, state: &<Self::Plugin as SCPlugin>::GameState, my_color: <Self::Plugin as SCPlugin>::PlayerColor, history: &GameHistory<Self::Plugin>, budget: &MoveBudget<<Self::Plugin as SCPlugin>::Move>) -> <Self::Plugin as SCPlugin>::Move;
+}
+
+/// A delegate that is lent to a background thread while it
+/// computes a move, which lets the client continue reading
+/// even if the delegate does not return in time. Hooks wait
+/// for a lent delegate for at most the move budget and are
+/// skipped afterwards until it has returned.
+pub(crate) struct SharedDelegate<D> {
+    lending: Arc<Lending<D>>
+}
+
+struct Lending<D> {
+    slot: Mutex<DelegateSlot<D>>,
+    returned: Condvar
+}
+
+struct DelegateSlot<D> {
+    /// The delegate, unless it is lent.
+    delegate: Option<D>,
+    /// How long hooks wait for a lent delegate.
+    patience: Duration,
+    /// Whether hooks have given up waiting.
+    stuck: bool
+}
+
+/// What the thread computing a move asks the client to send.
+enum Computed<M> {
+    /// The move returned by the delegate.
+    Move(M),
+    /// The latest candidate, since the delegate panicked.
+    Candidate(M),
+    /// A fallback move, since the delegate panicked or
+    /// was unavailable without publishing a candidate.
+    Fallback,
+    /// Nothing, since a move has already been sent.
+    Nothing
+}
+
+impl<D> SharedDelegate<D> {
+    fn new(delegate: D) -> Self {
+        Self {
+            lending: Arc::new(Lending {
+                slot: Mutex::new(DelegateSlot { delegate: Some(delegate), patience: DEFAULT_MOVE_BUDGET, stuck: false }),
+                returned: Condvar::new()
+            })
+        }
+    }
+
+    fn slot(&self) -> MutexGuard<'_, DelegateSlot<D>> {
+        self.lending.slot.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Invokes a hook with the delegate, waiting for it to
+    /// return if it is lent. Returns `None` if it did not
+    /// return in time.
+    fn with<T>(&self, hook: impl FnOnce(&mut D) -> T) -> Option<T> {
+        let mut slot = self.slot();
+        if slot.delegate.is_none() && !slot.stuck {
+            let patience = slot.patience;
+            slot = self.lending.returned.wait_timeout_while(slot, patience, |s| s.delegate.is_none())
+                .unwrap_or_else(PoisonError::into_inner).0;
+            if slot.delegate.is_none() {
+                warn!("Skipping hooks until the delegate has returned from computing a move");
+                slot.stuck = true;
+            }
+        }
+        slot.delegate.as_mut().map(hook)
+    }
+
+    /// Takes the delegate to compute a move, waiting until
+    /// the budget expires if it is still lent. Hooks wait for
+    /// the delegate for at most the given time afterwards.
+    fn lend<M>(&self, budget: &MoveBudget<M>, patience: Duration) -> Option<D> {
+        let slot = self.slot();
+        let mut slot = self.lending.returned.wait_timeout_while(slot, budget.remaining(), |s| s.delegate.is_none())
+            .unwrap_or_else(PoisonError::into_inner).0;
+        slot.patience = patience;
+        slot.delegate.take()
+    }
+
+    /// Returns a lent delegate.
+    fn give_back(&self, delegate: D) {
+        let mut slot = self.slot();
+        slot.delegate = Some(delegate);
+        slot.stuck = false;
+        self.lending.returned.notify_all();
+    }
+}
+
+impl<D> Clone for SharedDelegate<D> {
+    fn clone(&self) -> Self {
+        Self { lending: self.lending.clone() }
+    }
+}
+
+impl<D> DelegateHooks for SharedDelegate<D> where D: SCClientDelegate {
+    type Plugin = D::Plugin;
+
+    fn on_update_state(&mut self, state: &<Self::Plugin as SCPlugin>::GameState) { self.with(|d| d.on_update_state(state)); }
+    fn on_game_end(&mut self, result: GameResult<Self::Plugin>) { self.with(|d| d.on_game_end(result)); }
+    fn on_new_game(&mut self) { self.with(|d| d.on_new_game()); }
+    fn on_welcome_message(&mut self, color: &<Self::Plugin as SCPlugin>::PlayerColor) { self.with(|d| d.on_welcome_message(color)); }
+    fn on_joined(&mut self, room_id: &str) { self.with(|d| d.on_joined(room_id)); }
+    fn on_left(&mut self, room_id: &str) { self.with(|d| d.on_left(room_id)); }
+    fn on_server_error(&mut self, message: &str) { self.with(|d| d.on_server_error(message)); }
+    fn on_close(&mut self) { self.with(|d| d.on_close()); }
+    fn on_unrecognized_message(&mut self, node: &XmlNode) { self.with(|d| d.on_unrecognized_message(node)); }
+    fn on_opponent_move(&mut self, game_move: &<Self::Plugin as SCPlugin>::Move) { self.with(|d| d.on_opponent_move(game_move)); }
+    fn on_move_sent(&mut self, game_move: &<Self::Plugin as SCPlugin>::Move) { self.with(|d| d.on_move_sent(game_move)); }
+    fn move_metrics(&mut self) -> Vec<(String, f64)> { self.with(|d| d.move_metrics()).unwrap_or_default() }
+    fn ponder(&mut self, state: &<Self::Plugin as SCPlugin>::GameState, my_color: <Self::Plugin as SCPlugin>::PlayerColor) -> Option<PonderJob> { self.with(|d| d.ponder(state, my_color)).flatten() }
 }
 
 /// A configuration that determines whether
 /// the reader and/or the writer of a stream
-/// should be swapped by stdio to ease debugging.
+/// should be swapped by stdio and whether the
+/// exchanged messages should be recorded to
+/// a file to ease debugging.
+#[derive(Debug, Clone, Default)]
 pub struct DebugMode {
     pub debug_reader: bool,
-    pub debug_writer: bool
+    pub debug_writer: bool,
+    pub recording: Option<PathBuf>
+}
+
+impl DebugMode {
+    /// Swaps the reader and/or the writer
+    /// by stdio as configured.
+    fn apply<R, W>(&self, reader: R, writer: W) -> (Box<dyn Read>, Box<dyn Write + Send>) where R: Read + 'static, W: Write + Send + 'static {
+        let reader: Box<dyn Read> = if self.debug_reader { Box::new(io::stdin()) } else { Box::new(reader) };
+        let writer: Box<dyn Write + Send> = if self.debug_writer { Box::new(io::stdout()) } else { Box::new(writer) };
+        (reader, writer)
+    }
 }
 
 /// The client which handles XML requests, manages
-/// the game state and invokes the delegate.
+/// the game states and invokes the delegates. Each
+/// room the client plays in has its own delegate.
 pub struct SCClient<D> where D: SCClientDelegate {
-    delegate: D,
+    dispatcher: Dispatcher<SharedDelegate<D>>,
     debug_mode: DebugMode,
-    game_state: Option<<D::Plugin as SCPlugin>::GameState>
+    move_config: MoveConfig,
+    telemetry: Option<Box<dyn TelemetrySink<D::Plugin>>>,
+    game_count: usize,
+    recorder: Option<Recorder>
 }
 
 impl<D> SCClient<D> where D: SCClientDelegate {
     /// Creates a new client using the specified delegate.
     pub fn new(delegate: D, debug_mode: DebugMode) -> Self {
-        Self { delegate: delegate, debug_mode: debug_mode, game_state: None }
+        Self {
+            dispatcher: Dispatcher::new(SharedDelegate::new(delegate)),
+            debug_mode,
+            move_config: MoveConfig { budget: DEFAULT_MOVE_BUDGET, guard: false, watchdog: None },
+            telemetry: None,
+            game_count: 1,
+            recorder: None
+        }
+    }
+    
+    /// Sets the time the delegate may spend on a single move.
+    pub fn with_move_budget(mut self, move_budget: Duration) -> Self {
+        self.move_config.budget = move_budget;
+        self
+    }
+    
+    /// Enables validating moves before they are sent.
+    /// Illegal moves are replaced by the first legal
+    /// one, which is weaker, but avoids losing the game
+    /// due to a rule violation.
+    pub fn with_move_guard(mut self, move_guard: bool) -> Self {
+        self.move_config.guard = move_guard;
+        self
+    }
+    
+    /// Enables a watchdog sending a fallback move if the
+    /// delegate has neither returned a move nor published
+    /// a candidate within the given time. Should be longer
+    /// than the move budget.
+    pub fn with_watchdog(mut self, timeout: Duration) -> Self {
+        self.move_config.watchdog = Some(timeout);
+        self
+    }
+    
+    /// Sets the sink recording telemetry about each turn.
+    pub fn with_telemetry(mut self, sink: impl TelemetrySink<D::Plugin> + 'static) -> Self {
+        self.telemetry = Some(Box::new(sink));
+        self
+    }
+    
+    /// Sets the factory creating the delegates for rooms
+    /// joined after the first one, which is played by
+    /// the delegate passed to the constructor.
+    pub fn with_delegate_factory(mut self, mut delegate_factory: impl FnMut() -> D + Send + 'static) -> Self {
+        self.dispatcher.set_delegate_factory(Box::new(move || SharedDelegate::new(delegate_factory())));
+        self
+    }
+    
+    /// Sets the number of games `run` plays back-to-back.
+    /// After each game, the client reconnects and joins
+    /// the next one using the plugin's game type.
+    pub fn with_game_count(mut self, game_count: usize) -> Self {
+        self.game_count = game_count;
+        self
     }
     
     /// Blocks the thread and begins reading XML messages
-    /// from the provided address via TCP.
-    pub fn run(self, host: &str, port: u16, reservation: Option<&str>) -> SCResult<()> {
+    /// from the provided address via TCP. Returns a
+    /// summary of each played game.
+    pub fn run(mut self, host: &str, port: u16, reservation: Option<&str>) -> SCResult<Vec<GameSummary<D::Plugin>>> {
+        let address = format!("{}:{}", host, port);
+        let mut summaries = Vec::new();
+        
+        for game in 0..self.game_count {
+            let stream = TcpStream::connect(&address)?;
+            info!("Connected to {} for game {}/{}", address, game + 1, self.game_count);
+            
+            // Reservations are only valid for a single game
+            let reservation = reservation.filter(|_| game == 0);
+            summaries.extend(self.run_joining(stream, vec![dispatch::join_message::<D::Plugin>(reservation)])?);
+        }
+        
+        Ok(summaries)
+    }
+    
+    /// Blocks the thread and begins reading XML messages
+    /// from the provided transport. Returns a summary
+    /// of each played game.
+    pub fn run_with<T>(mut self, transport: T, reservation: Option<&str>) -> SCResult<Vec<GameSummary<D::Plugin>>> where T: Transport, T::Reader: 'static, T::Writer: 'static {
+        self.run_joining(transport, vec![dispatch::join_message::<D::Plugin>(reservation)])
+    }
+    
+    /// Blocks the thread and plays the prepared games with
+    /// the given reservation codes on a single connection
+    /// via TCP. Requires a delegate factory.
+    pub fn run_multiple(self, host: &str, port: u16, reservations: &[&str]) -> SCResult<Vec<GameSummary<D::Plugin>>> {
         let address = format!("{}:{}", host, port);
         let stream = TcpStream::connect(&address)?;
         info!("Connected to {}", address);
         
-        {
-            let mut writer = BufWriter::new(&stream);
-            writer.write("<protocol>".as_bytes())?;
-            
-            let join_xml = match reservation {
-                Some(res) => format!("<joinPrepared reservationCode=\"{}\" />", res),
-                None => format!("<join gameType=\"{}\" />", D::Plugin::protocol_game_type())
-            };
-            info!("Sending join message {}", join_xml);
-            writer.write(join_xml.as_bytes())?;
+        self.run_multiple_with(stream, reservations)
+    }
+    
+    /// Blocks the thread and plays the prepared games with
+    /// the given reservation codes on the provided transport.
+    pub fn run_multiple_with<T>(mut self, transport: T, reservations: &[&str]) -> SCResult<Vec<GameSummary<D::Plugin>>> where T: Transport, T::Reader: 'static, T::Writer: 'static {
+        let joins = reservations.iter().map(|&res| dispatch::join_message::<D::Plugin>(Some(res))).collect();
+        self.run_joining(transport, joins)
+    }
+    
+    /// Sends the join messages and begins
+    /// reading XML messages from the transport.
+    fn run_joining<T>(&mut self, transport: T, joins: Vec<XmlNode>) -> SCResult<Vec<GameSummary<D::Plugin>>> where T: Transport, T::Reader: 'static, T::Writer: 'static {
+        let (reader, mut writer) = transport.split()?;
+        writer.write_all("<protocol>".as_bytes())?;
+        
+        for join in &joins {
+            info!("Sending join message {}", join);
+            writer.write_all(join.to_string().as_bytes())?;
+        }
+        writer.flush()?;
+        
+        self.run_joined(reader, writer, &joins)
+    }
+    
+    /// Begins reading XML messages from a connection
+    /// on which the given join messages have been sent.
+    pub(crate) fn run_joined<R, W>(&mut self, reader: R, writer: W, joins: &[XmlNode]) -> SCResult<Vec<GameSummary<D::Plugin>>> where R: Read + 'static, W: Write + Send + 'static {
+        if let (None, Some(ref path)) = (&self.recorder, &self.debug_mode.recording) {
+            self.recorder = Some(Recorder::create(path)?);
         }
+        let mut recorder = self.recorder.take();
+        if let Some(ref mut recorder) = recorder {
+            for join in joins {
+                recorder.record(Direction::Sent, join)?;
+            }
+        }
+        self.dispatcher.begin_game();
         
-        // Begin parsing game messages from the stream.
-        // List all combinations of modes explicitly,
-        // since they generate different generic instantiations
-        // of `run_game`.
-
-        let mode = &self.debug_mode;
-        if mode.debug_reader && !mode.debug_writer {
-            self.run_game(io::stdin(), BufWriter::new(stream))?;
-        } else if !mode.debug_reader && mode.debug_writer {
-            self.run_game(BufReader::new(stream), io::stdout())?;
-        } else if mode.debug_reader && mode.debug_writer {
-            self.run_game(io::stdin(), io::stdout())?;
-        } else {
-            let reader = BufReader::new(stream.try_clone()?);
-            let writer = BufWriter::new(stream);
-            self.run_game(reader, writer)?;
+        // Begin parsing game messages from the transport.
+        let (reader, writer) = self.debug_mode.apply(reader, writer);
+        self.run_game(reader, writer, recorder)
+    }
+    
+    /// Blocks the thread and feeds the messages received
+    /// during a recorded session to the client, which lets
+    /// the delegate reproduce the game. Moves are discarded
+    /// instead of being sent.
+    pub fn replay(mut self, path: impl AsRef<Path>) -> SCResult<Vec<GameSummary<D::Plugin>>> {
+        let mut emitter_config = EmitterConfig::new();
+        emitter_config.write_document_declaration = false;
+
+        let mut xml_writer = emitter_config.create_writer(Vec::new());
+        xml_writer.inner_mut().write_all("<protocol>".as_bytes())?;
+        // Recordings of consecutive games contain a close
+        // message per game, which are replayed as one session
+        let messages = read_recording(path)?.into_iter()
+            .filter(|m| m.direction == Direction::Received)
+            .filter(|m| !matches!(m.node.name(), "close" | "sc.protocol.responses.CloseConnection"));
+        for message in messages {
+            message.node.write_to(&mut xml_writer)?;
         }
         
-        Ok(())
+        // Recordings of sessions that ended abruptly are
+        // closed as if the server had closed the connection
+        XmlNode::new("close").build().write_to(&mut xml_writer)?;
+        
+        info!("Replaying recorded session");
+        self.debug_mode = DebugMode::default();
+        self.run_with((Cursor::new(xml_writer.into_inner()), io::sink()), None)
     }
     
     /// Blocks the thread and parses/handles game messages
-    /// from the provided reader.
-    fn run_game<R, W>(mut self, reader: R, writer: W) -> SCResult<()> where R: Read, W: Write {
+    /// from the provided reader until the connection closes.
+    fn run_game<R, W>(&mut self, reader: R, writer: W, recorder: Option<Recorder>) -> SCResult<Vec<GameSummary<D::Plugin>>> where R: Read, W: Write + Send {
         let mut xml_reader = EventReader::new(reader);
 
         let mut emitter_config = EmitterConfig::new();
         emitter_config.write_document_declaration = false;
 
-        let mut xml_writer = emitter_config.create_writer(writer);
+        let mut outbox = Outbox { xml_writer: emitter_config.create_writer(writer), recorder };
+        let telemetry = self.telemetry.take().map(TelemetryWorker::start);
+        let result = self.handle_messages(&mut xml_reader, &mut outbox, telemetry.as_ref());
+        
+        // Keeps the recording, the telemetry sink and
+        // the delegates for the next game
+        self.recorder = outbox.recorder;
+        self.telemetry = telemetry.and_then(TelemetryWorker::finish);
+        let summaries = self.dispatcher.end_connection();
         
+        result.map(|()| summaries)
+    }
+    
+    /// Handles the messages of a connection.
+    fn handle_messages<R, W>(&mut self, xml_reader: &mut EventReader<R>, outbox: &mut Outbox<W>, telemetry: Option<&TelemetryWorker<D::Plugin>>) -> SCResult<()> where R: Read, W: Write {
         // Read initial protocol element
         info!("Waiting for initial <protocol>...");
-        while match xml_reader.next() {
-            Ok(XmlReadEvent::StartElement { name, .. }) => Some(name),
-            _ => None
-        }.filter(|n| n.local_name == "protocol").is_none() {}
+        loop {
+            match xml_reader.next()? {
+                XmlReadEvent::StartElement { name, .. } if name.local_name == "protocol" => break,
+                XmlReadEvent::EndDocument => return Err("Reached end of document while waiting for <protocol>".into()),
+                _ => ()
+            }
+        }
 
         loop {
-            let node = XmlNode::read_from(&mut xml_reader)?;
+            let node = XmlNode::read_from(xml_reader)?;
             debug!("Got XML node {}", node);
+            outbox.record(Direction::Received, &node)?;
             
-            match node.name() {
-                // Try parsing as room message (the game is running)
-                "room" => match <Room<D::Plugin>>::from_node(&node) {
-                    Ok(room) => match room.data {
-                        Data::WelcomeMessage { color } => {
-                            info!("Got welcome message with color: {:?}", color);
-                            self.delegate.on_welcome_message(&color);
-                        },
-                        Data::Memento { state } => {
-                            info!("Got updated game state");
-                            self.delegate.on_update_state(&state);
-                            self.game_state = Some(state);
-                        },
-                        Data::MoveRequest => {
-                            if let Some(ref state) = self.game_state {
-                                let turn = state.turn();
-                                let color = state.player_color();
-                                info!("Got move request @ turn: {}, color: {:?}", turn, color);
-
-                                let new_move = self.delegate.request_move(state, color);
-                                let move_node = XmlNode::try_from(Room::<D::Plugin> {
-                                    room_id: room.room_id,
-                                    data: Data::Move(new_move)
-                                })?;
-
-                                debug!("Sending move {}", move_node);
-                                move_node.write_to(&mut xml_writer)?;
-                                xml_writer.inner_mut().flush()?;
-                            } else {
-                                error!("Got move request, which cannot be fulfilled since no game state is present!");
-                            }
-                        },
-                        Data::GameResult(result) => {
-                            info!("Got game result: {:?}", result);
-                            self.delegate.on_game_end(result);
-                        },
-                        Data::Error { message } => {
-                            warn!("Got error from server: {}", message);
-                        },
-                        _ => warn!("Could not handle room data: {:?}", room.data)
-                    },
-                    Err(e) => error!("Could not parse node as room: {:?}", e)
+            match self.dispatcher.dispatch(&node) {
+                Dispatch::MoveRequest(room_id) => if let Some(session) = self.dispatcher.session(&room_id) {
+                    session.request_move(&room_id, self.move_config, telemetry, outbox)?;
                 },
+                Dispatch::Close => return Ok(()),
+                Dispatch::Continue => {}
+            }
+        }
+    }
+}
 
-                // Try parsing as 'joined' message
-                "joined" => match Joined::from_node(&node) {
-                    Ok(joined) => info!("Joined room {}", joined.room_id),
-                    Err(e) => error!("Could not parse node as 'joined': {:?}", e)
-                },
+impl<D> RoomSession<SharedDelegate<D>> where D: SCClientDelegate {
+    /// Requests a move from the delegate on a background thread
+    /// and sends it to the room, or the latest candidate published
+    /// to the budget once it expires. With the guard, illegal moves
+    /// are replaced by a legal fallback, which is also sent if the
+    /// delegate panics or the watchdog fires. The client continues
+    /// once a move is sent, even if the delegate has not returned.
+    fn request_move<W>(&mut self, room_id: &str, config: MoveConfig, telemetry: Option<&TelemetryWorker<D::Plugin>>, outbox: &mut Outbox<W>) -> SCResult<()> where W: Write {
+        let state = match self.history.current_state() {
+            Some(state) => state.clone(),
+            None => {
+                error!("Got move request, which cannot be fulfilled since no game state is present!");
+                return Ok(());
+            }
+        };
+        let turn = state.turn();
+        let color = state.player_color();
+        info!("Got move request in room {} @ turn: {}, color: {:?}", room_id, turn, color);
 
-                // Try parsing as 'left' message
-                "left" => match Left::from_node(&node) {
-                    Ok(left) => info!("Left room {}", left.room_id),
-                    Err(e) => error!("Could not parse node as 'left': {:?}", e)
-                },
-                
-                "close" | "sc.protocol.responses.CloseConnection" => {
-                    info!("Closing connection as requested by server...");
-                    break;
-                },
-                
-                _ => warn!("Unrecognized message: <{}>", node.name())
+        let started = Instant::now();
+        let budget = Arc::new(MoveBudget::new(started + config.budget));
+        let hard_deadline = config.watchdog.map(|timeout| started + timeout);
+        let computed = Self::compute_move(self.delegate.clone(), room_id, state.clone(), self.history.clone(), budget.clone(), config);
+        
+        // Sends a move, returning it along with the turn's stats
+        let send = |outbox: &mut Outbox<W>, game_move, timed_out, fallback| {
+            let game_move = if config.guard { dispatch::legal_move::<D::Plugin>(&state, game_move) } else { game_move };
+            let stats = TurnStats { turn, duration: started.elapsed(), timed_out, fallback };
+            Self::send_move(outbox, room_id, game_move).map(|m| Some((m, stats)))
+        };
+        let send_fallback = |outbox: &mut Outbox<W>, timed_out| match dispatch::fallback_move::<D::Plugin>(&state) {
+            Some(fallback) => send(outbox, fallback, timed_out, true),
+            None => {
+                error!("No fallback move available");
+                Ok(None)
             }
-        }
+        };
+
+        // Sends the latest candidate once the deadline is reached
+        // or a fallback once the hard deadline is reached
+        let sent_move = match budget.wait_for_expiry(hard_deadline) {
+            Expiry::Candidate(candidate) => {
+                info!("Reached deadline, sending latest candidate");
+                send(outbox, candidate, true, false)
+            },
+            Expiry::HardDeadline => {
+                error!("Delegate has not provided a move in time, sending fallback\nState: {:?}", state);
+                send_fallback(outbox, true)
+            },
+            Expiry::Finished => match computed.recv() {
+                Ok(Computed::Move(new_move)) => send(outbox, new_move, false, false),
+                Ok(Computed::Candidate(candidate)) => send(outbox, candidate, false, true),
+                Ok(Computed::Fallback) | Err(_) => send_fallback(outbox, false),
+                Ok(Computed::Nothing) => Ok(None)
+            }
+        }?;
         
+        if let Some((sent_move, stats)) = sent_move {
+            self.complete_turn(room_id, sent_move, stats, telemetry);
+        }
         Ok(())
     }
+
+    /// Lends the delegate to a detached thread computing
+    /// the move, which reports what has to be sent once
+    /// the budget is finished.
+    fn compute_move(delegate: SharedDelegate<D>, room_id: &str, state: <D::Plugin as SCPlugin>::GameState, history: GameHistory<D::Plugin>, budget: Arc<MoveBudget<<D::Plugin as SCPlugin>::Move>>, config: MoveConfig) -> mpsc::Receiver<Computed<<D::Plugin as SCPlugin>::Move>> {
+        let (sender, receiver) = mpsc::channel();
+        let room_id = room_id.to_owned();
+        thread::spawn(move || {
+            let _guard = budget.finish_guard();
+            let color = state.player_color();
+            let new_move = match delegate.lend(&budget, config.budget) {
+                Some(mut lent) => {
+                    let new_move = panic::catch_unwind(AssertUnwindSafe(|| lent.request_move(&state, color, &history, &budget)));
+                    delegate.give_back(lent);
+                    match new_move {
+                        Ok(new_move) => Some(new_move),
+                        Err(payload) => {
+                            error!("Delegate panicked while computing a move in room {}: {}\nState: {:?}", room_id, panic_message(&payload), state);
+                            None
+                        }
+                    }
+                },
+                None => {
+                    warn!("Delegate is still computing a previous move in room {}", room_id);
+                    None
+                }
+            };
+            let computed = match new_move {
+                Some(new_move) => match budget.finish(new_move) {
+                    Some(new_move) => Computed::Move(new_move),
+                    None => {
+                        warn!("Discarding move returned after the deadline, a candidate has already been sent");
+                        Computed::Nothing
+                    }
+                },
+                None => match budget.fail() {
+                    Some(Some(candidate)) => Computed::Candidate(candidate),
+                    Some(None) => Computed::Fallback,
+                    None => Computed::Nothing
+                }
+            };
+            let _ = sender.send(computed);
+        });
+        receiver
+    }
+    
+    /// Sends a move to the given room and returns it.
+    fn send_move<W>(outbox: &mut Outbox<W>, room_id: &str, game_move: <D::Plugin as SCPlugin>::Move) -> SCResult<<D::Plugin as SCPlugin>::Move> where W: Write {
+        let move_node = dispatch::move_message::<D::Plugin>(room_id, game_move.clone())?;
+        debug!("Sending move {}", move_node);
+        outbox.send(&move_node)?;
+        Ok(game_move)
+    }
+}
+
+/// The writing half of a connection, which
+/// also records exchanged messages if enabled.
+struct Outbox<W> where W: Write {
+    xml_writer: EventWriter<W>,
+    recorder: Option<Recorder>
+}
+
+impl<W> Outbox<W> where W: Write {
+    /// Sends and records a message.
+    fn send(&mut self, node: &XmlNode) -> SCResult<()> {
+        node.write_to(&mut self.xml_writer)?;
+        self.xml_writer.inner_mut().flush()?;
+        self.record(Direction::Sent, node)
+    }
+    
+    /// Records a message if enabled.
+    fn record(&mut self, direction: Direction, node: &XmlNode) -> SCResult<()> {
+        match self.recorder {
+            Some(ref mut recorder) => recorder.record(direction, node),
+            None => Ok(())
+        }
+    }
 }
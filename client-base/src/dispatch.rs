@@ -0,0 +1,374 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::convert::TryFrom;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+use log::{info, warn, error};
+use crate::history::GameHistory;
+use crate::plugin::{SCPlugin, HasPlayerColor, HasRules};
+use crate::ponder::{PonderJob, Pondering};
+use crate::protocol::{Joined, Left, Room, Data, GameResult};
+use crate::summary::{GameSummary, TurnStats};
+use crate::telemetry::{TelemetryWorker, TurnRecord};
+use crate::util::SCResult;
+use crate::xml_node::{XmlNode, FromXmlNode};
+
+/// The delegate hooks invoked while dispatching messages.
+/// Both the synchronous and the asynchronous delegate
+/// traits are adapted to it, which lets the clients
+/// share everything except requesting moves. Panics
+/// in hooks are caught and logged.
+pub(crate) trait DelegateHooks {
+    type Plugin: SCPlugin;
+
+    fn on_update_state(&mut self, state: &<Self::Plugin as SCPlugin>::GameState);
+    fn on_game_end(&mut self, result: GameResult<Self::Plugin>);
+    fn on_new_game(&mut self);
+    fn on_welcome_message(&mut self, color: &<Self::Plugin as SCPlugin>::PlayerColor);
+    fn on_joined(&mut self, room_id: &str);
+    fn on_left(&mut self, room_id: &str);
+    fn on_server_error(&mut self, message: &str);
+    fn on_close(&mut self);
+    fn on_unrecognized_message(&mut self, node: &XmlNode);
+    fn on_opponent_move(&mut self, game_move: &<Self::Plugin as SCPlugin>::Move);
+    fn on_move_sent(&mut self, game_move: &<Self::Plugin as SCPlugin>::Move);
+    fn move_metrics(&mut self) -> Vec<(String, f64)>;
+    fn ponder(&mut self, state: &<Self::Plugin as SCPlugin>::GameState, my_color: <Self::Plugin as SCPlugin>::PlayerColor) -> Option<PonderJob>;
+}
+
+/// Determines how moves are requested from delegates.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MoveConfig {
+    pub(crate) budget: Duration,
+    pub(crate) guard: bool,
+    pub(crate) watchdog: Option<Duration>
+}
+
+/// What the client has to do after a
+/// message has been dispatched.
+pub(crate) enum Dispatch {
+    /// Keep reading messages.
+    Continue,
+    /// Request a move from the delegate of the given room.
+    MoveRequest(String),
+    /// Close the connection.
+    Close
+}
+
+/// Decodes the messages received by a client and passes
+/// them to the delegates of the rooms it plays in. Each
+/// client requests moves itself, since the synchronous
+/// and the asynchronous client wait for them differently.
+pub(crate) struct Dispatcher<D> where D: DelegateHooks {
+    /// The delegates not playing in a room.
+    delegates: Vec<D>,
+    delegate_factory: Option<Box<dyn FnMut() -> D + Send>>,
+    rooms: HashMap<String, RoomSession<D>>,
+    summaries: Vec<GameSummary<D::Plugin>>
+}
+
+/// The delegate and the game state of a single room.
+pub(crate) struct RoomSession<D> where D: DelegateHooks {
+    pub(crate) delegate: D,
+    pub(crate) my_color: Option<<D::Plugin as SCPlugin>::PlayerColor>,
+    pub(crate) history: GameHistory<D::Plugin>,
+    pondering: Option<Pondering>,
+    turns: Vec<TurnStats>
+}
+
+impl<D> Dispatcher<D> where D: DelegateHooks {
+    /// Creates a dispatcher playing the first
+    /// room with the given delegate.
+    pub(crate) fn new(delegate: D) -> Self {
+        Self { delegates: vec![delegate], delegate_factory: None, rooms: HashMap::new(), summaries: Vec::new() }
+    }
+
+    /// Sets the factory creating the delegates
+    /// for rooms joined after the first one.
+    pub(crate) fn set_delegate_factory(&mut self, delegate_factory: Box<dyn FnMut() -> D + Send>) {
+        self.delegate_factory = Some(delegate_factory);
+    }
+
+    /// Notifies the delegates that a new game begins.
+    pub(crate) fn begin_game(&mut self) {
+        for delegate in &mut self.delegates {
+            isolate("on_new_game", || delegate.on_new_game());
+        }
+    }
+
+    /// Returns the summaries of the games played on the
+    /// connection and keeps the delegates for the next one.
+    pub(crate) fn end_connection(&mut self) -> Vec<GameSummary<D::Plugin>> {
+        for (_, mut session) in self.rooms.drain() {
+            session.stop_pondering();
+            self.delegates.push(session.delegate);
+        }
+        mem::take(&mut self.summaries)
+    }
+
+    /// Handles a received message.
+    pub(crate) fn dispatch(&mut self, node: &XmlNode) -> Dispatch {
+        match node.name() {
+            // Try parsing as room message (the game is running)
+            "room" => match <Room<D::Plugin>>::from_node(node) {
+                Ok(room) => return self.dispatch_room(room, node),
+                Err(e) => {
+                    error!("Could not parse node as room: {:?}", e);
+                    match node.attribute("roomId") {
+                        Ok(room_id) => if let Some(session) = self.session(room_id) {
+                            let delegate = &mut session.delegate;
+                            isolate("on_unrecognized_message", || delegate.on_unrecognized_message(node));
+                        },
+                        Err(_) => self.for_each_delegate("on_unrecognized_message", |delegate| delegate.on_unrecognized_message(node))
+                    }
+                }
+            },
+
+            // Try parsing as 'joined' message
+            "joined" => match Joined::from_node(node) {
+                Ok(joined) => {
+                    info!("Joined room {}", joined.room_id);
+                    if let Some(session) = self.session(&joined.room_id) {
+                        let delegate = &mut session.delegate;
+                        isolate("on_joined", || delegate.on_joined(&joined.room_id));
+                    }
+                },
+                Err(e) => error!("Could not parse node as 'joined': {:?}", e)
+            },
+
+            // Try parsing as 'left' message
+            "left" => match Left::from_node(node) {
+                Ok(left) => {
+                    info!("Left room {}", left.room_id);
+                    if let Some(session) = self.session(&left.room_id) {
+                        session.stop_pondering();
+                        let delegate = &mut session.delegate;
+                        isolate("on_left", || delegate.on_left(&left.room_id));
+                    }
+                },
+                Err(e) => error!("Could not parse node as 'left': {:?}", e)
+            },
+
+            "close" | "sc.protocol.responses.CloseConnection" => {
+                info!("Closing connection as requested by server...");
+                self.rooms.values_mut().for_each(RoomSession::stop_pondering);
+                self.for_each_delegate("on_close", |delegate| delegate.on_close());
+                return Dispatch::Close;
+            },
+
+            _ => {
+                warn!("Unrecognized message: <{}>", node.name());
+                self.for_each_delegate("on_unrecognized_message", |delegate| delegate.on_unrecognized_message(node));
+            }
+        }
+        Dispatch::Continue
+    }
+
+    /// Handles the data sent to a room.
+    fn dispatch_room(&mut self, room: Room<D::Plugin>, node: &XmlNode) -> Dispatch {
+        let session = match self.session(&room.room_id) {
+            Some(session) => session,
+            None => return Dispatch::Continue
+        };
+        let delegate = &mut session.delegate;
+        match room.data {
+            Data::WelcomeMessage { color } => {
+                info!("Got welcome message in room {} with color: {:?}", room.room_id, color);
+                isolate("on_welcome_message", || delegate.on_welcome_message(&color));
+                session.my_color = Some(color);
+            },
+            Data::Memento { state } => {
+                info!("Got updated game state in room {}", room.room_id);
+                session.stop_pondering();
+                session.infer_opponent_move(&state);
+                let delegate = &mut session.delegate;
+                if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| delegate.on_update_state(&state))) {
+                    error!("Delegate panicked while updating the state in room {}: {}\nState: {:?}", room.room_id, panic_message(&payload), state);
+                }
+                session.start_pondering(&state);
+                session.history.push_state(state);
+            },
+            Data::MoveRequest => return Dispatch::MoveRequest(room.room_id),
+            Data::GameResult(result) => {
+                info!("Got game result in room {}: {:?}", room.room_id, result);
+                session.stop_pondering();
+                let delegate = &mut session.delegate;
+                isolate("on_game_end", || delegate.on_game_end(result.clone()));
+                let summary = GameSummary {
+                    room_id: room.room_id,
+                    my_color: session.my_color,
+                    result,
+                    final_state: session.history.current_state().cloned(),
+                    turns: mem::take(&mut session.turns),
+                    history: mem::take(&mut session.history)
+                };
+                self.summaries.push(summary);
+            },
+            Data::Error { message } => {
+                warn!("Got error from server in room {}: {}", room.room_id, message);
+                isolate("on_server_error", || delegate.on_server_error(&message));
+            },
+            data => {
+                warn!("Could not handle room data: {:?}", data);
+                isolate("on_unrecognized_message", || delegate.on_unrecognized_message(node));
+            }
+        }
+        Dispatch::Continue
+    }
+
+    /// Fetches the session of a room, which is created with
+    /// a new delegate on first use. Returns `None` if no
+    /// delegate is left for an unknown room, in which case
+    /// its messages are skipped.
+    pub(crate) fn session(&mut self, room_id: &str) -> Option<&mut RoomSession<D>> {
+        match self.rooms.entry(room_id.to_owned()) {
+            Entry::Occupied(entry) => Some(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                let delegate = match (self.delegates.pop(), self.delegate_factory.as_mut()) {
+                    (Some(delegate), _) => delegate,
+                    (None, Some(delegate_factory)) => delegate_factory(),
+                    (None, None) => {
+                        warn!("Skipping message for room {}, since playing multiple rooms requires a delegate factory", room_id);
+                        return None;
+                    }
+                };
+                info!("Playing in room {}", room_id);
+                Some(entry.insert(RoomSession::new(delegate)))
+            }
+        }
+    }
+
+    /// Invokes the given hook with the delegate of each room
+    /// or, before any room is known, the idle delegates.
+    fn for_each_delegate(&mut self, hook: &str, mut callback: impl FnMut(&mut D)) {
+        let mut invoke = |delegate: &mut D| { isolate(hook, || callback(delegate)); };
+        if self.rooms.is_empty() {
+            self.delegates.iter_mut().for_each(invoke);
+        } else {
+            self.rooms.values_mut().for_each(|session| invoke(&mut session.delegate));
+        }
+    }
+}
+
+impl<D> RoomSession<D> where D: DelegateHooks {
+    fn new(delegate: D) -> Self {
+        Self { delegate, my_color: None, history: GameHistory::new(), pondering: None, turns: Vec::new() }
+    }
+
+    /// Notifies the delegate about the sent move and records
+    /// the turn, which is also passed to the telemetry worker
+    /// if one is running.
+    pub(crate) fn complete_turn(&mut self, room_id: &str, sent_move: <D::Plugin as SCPlugin>::Move, stats: TurnStats, telemetry: Option<&TelemetryWorker<D::Plugin>>) {
+        let turn = stats.turn;
+        let delegate = &mut self.delegate;
+        isolate("on_move_sent", || delegate.on_move_sent(&sent_move));
+        self.turns.push(stats.clone());
+
+        if let (Some(telemetry), Some(state)) = (telemetry, self.history.current_state()) {
+            telemetry.record(TurnRecord {
+                room_id: room_id.to_owned(),
+                color: state.player_color(),
+                stats,
+                state: state.clone(),
+                sent_move: sent_move.clone(),
+                metrics: isolate("move_metrics", || delegate.move_metrics()).unwrap_or_default()
+            });
+        }
+        self.history.push_move(turn, sent_move);
+    }
+
+    /// Passes the opponent's move to the delegate if
+    /// the previous state was the opponent's turn.
+    fn infer_opponent_move(&mut self, next: &<D::Plugin as SCPlugin>::GameState) {
+        if let (Some(previous), Some(my_color)) = (self.history.current_state(), self.my_color) {
+            if previous.player_color() != my_color {
+                match previous.infer_move(next) {
+                    Some(game_move) => {
+                        let delegate = &mut self.delegate;
+                        isolate("on_opponent_move", || delegate.on_opponent_move(&game_move));
+                    },
+                    None => warn!("Could not infer the opponent's move")
+                }
+            }
+        }
+    }
+
+    /// Starts pondering if it is the opponent's
+    /// turn and the delegate provides a job.
+    fn start_pondering(&mut self, state: &<D::Plugin as SCPlugin>::GameState) {
+        if let Some(my_color) = self.my_color {
+            if state.player_color() != my_color {
+                let delegate = &mut self.delegate;
+                self.pondering = isolate("ponder", || delegate.ponder(state, my_color)).flatten().map(Pondering::start);
+            }
+        }
+    }
+
+    /// Cancels the running ponder job, if any.
+    fn stop_pondering(&mut self) {
+        if let Some(pondering) = self.pondering.take() {
+            pondering.stop();
+        }
+    }
+}
+
+/// Fetches the first legal move, if any.
+pub(crate) fn fallback_move<P>(state: &P::GameState) -> Option<P::Move> where P: SCPlugin {
+    state.possible_moves().into_iter().next()
+}
+
+/// Replaces an illegal move by the first legal one.
+pub(crate) fn legal_move<P>(state: &P::GameState, game_move: P::Move) -> P::Move where P: SCPlugin {
+    match state.validate_move(&game_move) {
+        Ok(()) => game_move,
+        Err(e) => match fallback_move::<P>(state) {
+            Some(fallback) => {
+                warn!("Replacing illegal move {:?} ({}) by {:?}", game_move, e, fallback);
+                fallback
+            },
+            None => {
+                warn!("Sending illegal move {:?} ({}), since no legal move is available", game_move, e);
+                game_move
+            }
+        }
+    }
+}
+
+/// Creates the message joining a game, either the prepared
+/// one with the given reservation or any of the plugin's type.
+pub(crate) fn join_message<P>(reservation: Option<&str>) -> XmlNode where P: SCPlugin {
+    match reservation {
+        Some(res) => XmlNode::new("joinPrepared").attribute("reservationCode", res).build(),
+        None => XmlNode::new("join").attribute("gameType", P::protocol_game_type()).build()
+    }
+}
+
+/// Creates the message sending a move to the given room.
+pub(crate) fn move_message<P>(room_id: &str, game_move: P::Move) -> SCResult<XmlNode> where P: SCPlugin {
+    XmlNode::try_from(Room::<P> {
+        room_id: room_id.to_owned(),
+        data: Data::Move(game_move)
+    })
+}
+
+/// Invokes a delegate hook, logging a panic instead of
+/// propagating it. Returns `None` if the hook panicked.
+pub(crate) fn isolate<T>(hook: &str, callback: impl FnOnce() -> T) -> Option<T> {
+    match panic::catch_unwind(AssertUnwindSafe(callback)) {
+        Ok(value) => Some(value),
+        Err(payload) => {
+            error!("Delegate panicked in {}: {}", hook, panic_message(&payload));
+            None
+        }
+    }
+}
+
+/// Extracts the message of a caught panic.
+pub(crate) fn panic_message(payload: &Box<dyn Any + Send>) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => payload.downcast_ref::<String>().map_or("<unknown>", String::as_str)
+    }
+}
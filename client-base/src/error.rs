@@ -1,3 +1,5 @@
+use std::error::Error;
+use std::fmt;
 use std::io::Error as IoError;
 use std::str::ParseBoolError;
 use std::num::{ParseIntError, ParseFloatError};
@@ -7,7 +9,9 @@ use xml::writer::Error as XmlWriterError;
 /// A custom error type that abstracts over
 /// other errors (such as IO/XML errors) and
 /// can conveniently be used in conjunction with
-/// `Result`.
+/// `Result`. Protocol errors carry the path of
+/// the offending element, e.g.
+/// `room/data[0]/state[0]/board[0]/field[17]`.
 #[derive(Debug)]
 pub enum SCError {
     Io(IoError),
@@ -16,9 +20,61 @@ pub enum SCError {
     ParseInt(ParseIntError),
     ParseFloat(ParseFloatError),
     ParseBool(ParseBoolError),
+    /// An element lacks a required attribute.
+    MissingAttribute { path: String, key: String },
+    /// An element lacks a required child element.
+    MissingChild { path: String, name: String },
+    /// An attribute or an element's content could not
+    /// be parsed, e.g. because of an unknown enum value.
+    InvalidValue { path: String, value: String, message: String },
+    /// A data element has a class that is neither
+    /// known to the protocol nor to the plugin.
+    UnknownDataClass { path: String, class: String },
     Custom(String)
 }
 
+impl fmt::Display for SCError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "IO error: {}", e),
+            Self::XmlReader(e) => write!(f, "Could not read XML: {}", e),
+            Self::XmlWriter(e) => write!(f, "Could not write XML: {}", e),
+            Self::ParseInt(e) => write!(f, "Could not parse integer: {}", e),
+            Self::ParseFloat(e) => write!(f, "Could not parse float: {}", e),
+            Self::ParseBool(e) => write!(f, "Could not parse bool: {}", e),
+            Self::MissingAttribute { path, key } => write!(f, "No attribute with key '{}' found in {}", key, path),
+            Self::MissingChild { path, name } => write!(f, "No <{}> found in {}", name, path),
+            Self::InvalidValue { path, value, message } => write!(f, "Invalid value '{}' in {}: {}", value, path, message),
+            Self::UnknownDataClass { path, class } => write!(f, "Unrecognized data class '{}' in {}", class, path),
+            Self::Custom(message) => write!(f, "{}", message)
+        }
+    }
+}
+
+impl Error for SCError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::XmlReader(e) => Some(e),
+            Self::XmlWriter(e) => Some(e),
+            Self::ParseInt(e) => Some(e),
+            Self::ParseFloat(e) => Some(e),
+            Self::ParseBool(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for SCError {
+    fn custom<T>(message: T) -> Self where T: fmt::Display { Self::Custom(message.to_string()) }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for SCError {
+    fn custom<T>(message: T) -> Self where T: fmt::Display { Self::Custom(message.to_string()) }
+}
+
 impl From<IoError> for SCError {
     fn from(error: IoError) -> Self { Self::Io(error) }
 }
@@ -0,0 +1,62 @@
+use std::fmt;
+use crate::plugin::{HasTurn, SCPlugin};
+
+/// The states received and the moves sent
+/// during a game, in chronological order.
+pub struct GameHistory<P> where P: SCPlugin {
+    states: Vec<P::GameState>,
+    moves: Vec<(u32, P::Move)>
+}
+
+impl<P> GameHistory<P> where P: SCPlugin {
+    /// Creates an empty history.
+    pub fn new() -> Self {
+        Self { states: Vec::new(), moves: Vec::new() }
+    }
+
+    /// Appends a received state.
+    pub fn push_state(&mut self, state: P::GameState) {
+        self.states.push(state);
+    }
+
+    /// Appends a move sent in the given turn.
+    pub fn push_move(&mut self, turn: u32, game_move: P::Move) {
+        self.moves.push((turn, game_move));
+    }
+
+    /// Fetches the received states.
+    pub fn states(&self) -> &[P::GameState] { &self.states }
+
+    /// Fetches the sent moves along with their turns.
+    pub fn moves(&self) -> &[(u32, P::Move)] { &self.moves }
+
+    /// Fetches the latest state.
+    pub fn current_state(&self) -> Option<&P::GameState> { self.states.last() }
+
+    /// Fetches the latest state received in the given turn.
+    pub fn state_at_turn(&self, turn: u32) -> Option<&P::GameState> {
+        self.states.iter().rev().find(|s| s.turn() == turn)
+    }
+
+    /// Counts how often the given state has been received,
+    /// which can be used to detect repetitions.
+    pub fn occurrences(&self, state: &P::GameState) -> usize {
+        self.states.iter().filter(|&s| s == state).count()
+    }
+}
+
+impl<P> Default for GameHistory<P> where P: SCPlugin {
+    fn default() -> Self { Self::new() }
+}
+
+impl<P> Clone for GameHistory<P> where P: SCPlugin {
+    fn clone(&self) -> Self {
+        Self { states: self.states.clone(), moves: self.moves.clone() }
+    }
+}
+
+impl<P> fmt::Debug for GameHistory<P> where P: SCPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GameHistory").field("states", &self.states).field("moves", &self.moves).finish()
+    }
+}
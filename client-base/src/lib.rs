@@ -1,6 +1,25 @@
+// Lets the derived implementations refer to this crate by name
+extern crate self as socha_client_base;
+
+pub mod admin_client;
 pub mod client;
+mod dispatch;
+pub mod history;
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod move_budget;
+pub mod multi_client;
+pub mod observer;
+pub mod ponder;
 pub mod plugin;
 pub mod protocol;
+pub mod recording;
+pub mod runner;
+pub mod summary;
+pub mod telemetry;
+pub mod transport;
 pub mod xml_node;
+#[cfg(feature = "serde")]
+pub mod xml_serde;
 pub mod error;
 pub mod util;
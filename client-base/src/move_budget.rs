@@ -0,0 +1,163 @@
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// The default time a delegate may spend on a move. It
+/// stays below the server's soft timeout of 2 seconds to
+/// leave some headroom for the network.
+pub const DEFAULT_MOVE_BUDGET: Duration = Duration::from_millis(1500);
+
+/// The time budget for a single move request. Delegates
+/// can publish improving candidate moves while searching.
+/// Once the deadline is reached, the client sends the latest
+/// candidate on the delegate's behalf, which makes anytime
+/// searches (such as iterative deepening) safe.
+pub struct MoveBudget<M> {
+    deadline: Instant,
+    slot: Mutex<CandidateSlot<M>>,
+    changed: Condvar
+}
+
+/// Marks the budget as finished once dropped, which
+/// also stops the watchdog if the delegate panics.
+pub(crate) struct FinishGuard<'a, M>(&'a MoveBudget<M>);
+
+/// The outcome of waiting for a move request to expire.
+pub(crate) enum Expiry<M> {
+    /// The deadline has passed and the latest candidate is due.
+    Candidate(M),
+    /// The hard deadline has passed without any candidate.
+    HardDeadline,
+    /// The delegate has finished before.
+    Finished
+}
+
+struct CandidateSlot<M> {
+    candidate: Option<M>,
+    sent: bool,
+    finished: bool
+}
+
+impl<M> MoveBudget<M> {
+    /// Creates a new budget expiring at the given deadline.
+    pub fn new(deadline: Instant) -> Self {
+        Self {
+            deadline,
+            slot: Mutex::new(CandidateSlot { candidate: None, sent: false, finished: false }),
+            changed: Condvar::new()
+        }
+    }
+
+    /// Fetches the point in time at which the
+    /// latest candidate will be sent.
+    pub fn deadline(&self) -> Instant { self.deadline }
+
+    /// Fetches the remaining time until the deadline.
+    pub fn remaining(&self) -> Duration { self.deadline.saturating_duration_since(Instant::now()) }
+
+    /// Whether the deadline has passed or a candidate
+    /// has already been sent. Delegates should stop
+    /// searching once this returns true.
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.deadline || self.slot.lock().unwrap().sent
+    }
+
+    /// Publishes a new best-so-far move, replacing the
+    /// previous candidate.
+    pub fn publish(&self, candidate: M) {
+        let mut slot = self.slot.lock().unwrap();
+        if !slot.sent {
+            slot.candidate = Some(candidate);
+            self.changed.notify_all();
+        }
+    }
+
+    /// Blocks until the deadline has passed and a candidate
+    /// is available, the optional hard deadline has passed
+    /// without a candidate or the delegate has finished.
+    pub(crate) fn wait_for_expiry(&self, hard_deadline: Option<Instant>) -> Expiry<M> {
+        let mut slot = self.slot.lock().unwrap();
+        loop {
+            if slot.finished {
+                return Expiry::Finished;
+            }
+            let now = Instant::now();
+            if now >= self.deadline {
+                if let Some(candidate) = slot.candidate.take() {
+                    slot.sent = true;
+                    return Expiry::Candidate(candidate);
+                }
+                slot = match hard_deadline {
+                    Some(hard_deadline) if now >= hard_deadline => {
+                        slot.sent = true;
+                        return Expiry::HardDeadline;
+                    },
+                    Some(hard_deadline) => self.changed.wait_timeout(slot, hard_deadline - now).unwrap().0,
+                    None => self.changed.wait(slot).unwrap()
+                };
+            } else {
+                slot = self.changed.wait_timeout(slot, self.deadline - now).unwrap().0;
+            }
+        }
+    }
+
+    /// Checks without blocking whether the deadline has passed
+    /// and a candidate is due or the optional hard deadline has
+    /// passed without a candidate, while the delegate is still
+    /// running. Lets the asynchronous client poll for expiry.
+    #[cfg(feature = "async")]
+    pub(crate) fn poll_expiry(&self, hard_deadline: Option<Instant>) -> Option<Expiry<M>> {
+        let mut slot = self.slot.lock().unwrap();
+        let now = Instant::now();
+        if slot.finished || slot.sent || now < self.deadline {
+            return None;
+        }
+        if let Some(candidate) = slot.candidate.take() {
+            slot.sent = true;
+            return Some(Expiry::Candidate(candidate));
+        }
+        match hard_deadline {
+            Some(hard_deadline) if now >= hard_deadline => {
+                slot.sent = true;
+                Some(Expiry::HardDeadline)
+            },
+            _ => None
+        }
+    }
+
+    /// Creates a guard that marks the request
+    /// as finished when dropped.
+    pub(crate) fn finish_guard(&self) -> FinishGuard<'_, M> { FinishGuard(self) }
+
+    /// Marks the request as finished with the delegate's final
+    /// move, which is returned if no candidate has been sent yet.
+    pub(crate) fn finish(&self, final_move: M) -> Option<M> {
+        let mut slot = self.slot.lock().unwrap();
+        slot.finished = true;
+        self.changed.notify_all();
+        if slot.sent { None } else { Some(final_move) }
+    }
+
+    /// Marks the request as failed, e.g. since the delegate
+    /// panicked. Returns `None` if a move has already been
+    /// sent and the unsent candidate (if any) otherwise.
+    pub(crate) fn fail(&self) -> Option<Option<M>> {
+        let mut slot = self.slot.lock().unwrap();
+        slot.finished = true;
+        self.changed.notify_all();
+        if slot.sent {
+            None
+        } else {
+            slot.sent = true;
+            Some(slot.candidate.take())
+        }
+    }
+}
+
+impl<'a, M> Drop for FinishGuard<'a, M> {
+    fn drop(&mut self) {
+        if let Ok(mut slot) = self.0.slot.lock() {
+            slot.finished = true;
+            self.0.changed.notify_all();
+        }
+    }
+}
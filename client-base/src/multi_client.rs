@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::io::{self, Cursor, Read, Write};
+use std::net::TcpStream;
+use log::info;
+use xml::reader::{XmlEvent as XmlReadEvent, EventReader};
+use crate::client::{SCClient, SCClientDelegate};
+use crate::plugin::SCPlugin;
+use crate::protocol::{Joined, PlayerScore, Room};
+use crate::summary::TurnStats;
+use crate::transport::Transport;
+use crate::xml_node::{FromXmlNode, XmlNode};
+use crate::util::SCResult;
+
+/// Determines the game a multi-plugin client joins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinTarget<'a> {
+    /// Joins any game of the given type.
+    GameType(&'a str),
+    /// Joins the prepared game holding the reservation.
+    Reservation(&'a str)
+}
+
+/// The outcome of a game played by a
+/// multi-plugin client, independent of
+/// the plugin's types.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginGameSummary {
+    pub game_type: String,
+    pub room_id: String,
+    pub my_score: Option<PlayerScore>,
+    pub did_win: bool,
+    pub rank: Option<usize>,
+    pub turns: Vec<TurnStats>
+}
+
+/// A client bound to a single plugin,
+/// with its types hidden from the registry.
+trait PluginClient {
+    /// Whether the plugin can parse the given room message.
+    fn accepts(&self, node: &XmlNode) -> bool;
+
+    /// Plays on a connection on which the
+    /// given join messages have been sent.
+    fn play(&mut self, reader: Box<dyn Read>, writer: Box<dyn Write + Send>, joins: &[XmlNode]) -> SCResult<Vec<PluginGameSummary>>;
+}
+
+/// A client holding a registry of clients keyed by
+/// their plugin's game type, which selects the plugin
+/// once the server has revealed the game. This lets
+/// a single binary play games of several seasons.
+#[derive(Default)]
+pub struct MultiPluginClient {
+    clients: HashMap<&'static str, Box<dyn PluginClient>>
+}
+
+/// A reader keeping a copy of the bytes read, which
+/// lets them be read again after peeking.
+struct TeeReader<R> where R: Read {
+    inner: R,
+    read: Vec<u8>
+}
+
+impl<D> PluginClient for SCClient<D> where D: SCClientDelegate {
+    fn accepts(&self, node: &XmlNode) -> bool {
+        <Room<D::Plugin>>::from_node(node).is_ok()
+    }
+
+    fn play(&mut self, reader: Box<dyn Read>, writer: Box<dyn Write + Send>, joins: &[XmlNode]) -> SCResult<Vec<PluginGameSummary>> {
+        let summaries = self.run_joined(reader, writer, joins)?;
+        Ok(summaries.into_iter().map(|summary| PluginGameSummary {
+            game_type: D::Plugin::protocol_game_type().to_owned(),
+            my_score: summary.my_score().cloned(),
+            did_win: summary.did_win(),
+            rank: summary.rank(),
+            room_id: summary.room_id,
+            turns: summary.turns
+        }).collect())
+    }
+}
+
+impl MultiPluginClient {
+    /// Creates a client without any plugins.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the client playing games of its plugin's
+    /// type, replacing a client registered for the same type.
+    pub fn with_client<D>(mut self, client: SCClient<D>) -> Self where D: SCClientDelegate + 'static {
+        self.clients.insert(D::Plugin::protocol_game_type(), Box::new(client));
+        self
+    }
+
+    /// Fetches the registered game types.
+    pub fn game_types(&self) -> Vec<&str> {
+        let mut game_types: Vec<_> = self.clients.keys().copied().collect();
+        game_types.sort_unstable();
+        game_types
+    }
+
+    /// Blocks the thread and plays the joined game via TCP.
+    /// Returns a summary of each played game.
+    pub fn run(&mut self, host: &str, port: u16, target: JoinTarget) -> SCResult<Vec<PluginGameSummary>> {
+        let address = format!("{}:{}", host, port);
+        let stream = TcpStream::connect(&address)?;
+        info!("Connected to {}", address);
+
+        self.run_with(stream, target)
+    }
+
+    /// Blocks the thread and plays the joined game on the
+    /// provided transport. If the game type is not known in
+    /// advance, it is taken from the join response or, if the
+    /// server does not report it, from the first room message
+    /// one of the plugins can parse.
+    pub fn run_with<T>(&mut self, transport: T, target: JoinTarget) -> SCResult<Vec<PluginGameSummary>> where T: Transport, T::Reader: 'static, T::Writer: 'static {
+        let (join, game_type) = match target {
+            JoinTarget::GameType(game_type) => (
+                XmlNode::new("join").attribute("gameType", game_type).build(),
+                Some(self.registered_game_type(game_type)?)
+            ),
+            JoinTarget::Reservation(reservation) => (
+                XmlNode::new("joinPrepared").attribute("reservationCode", reservation).build(),
+                None
+            )
+        };
+
+        let (reader, mut writer) = transport.split()?;
+        writer.write_all("<protocol>".as_bytes())?;
+        info!("Sending join message {}", join);
+        writer.write_all(join.to_string().as_bytes())?;
+        writer.flush()?;
+
+        let (game_type, reader): (_, Box<dyn Read>) = match game_type {
+            Some(game_type) => (game_type, Box::new(reader)),
+            None => {
+                // The peeked messages are read again by the selected client
+                let mut xml_reader = EventReader::new(TeeReader { inner: reader, read: Vec::new() });
+                let game_type = self.peek_game_type(&mut xml_reader)?;
+                let TeeReader { inner, read } = xml_reader.into_inner();
+                (game_type, Box::new(Cursor::new(read).chain(inner)))
+            }
+        };
+
+        info!("Playing {} with the matching plugin", game_type);
+        let client = self.clients.get_mut(game_type).expect("Game type is registered");
+        client.play(reader, Box::new(writer), &[join])
+    }
+
+    /// Fetches the registered key for the given game type.
+    fn registered_game_type(&self, game_type: &str) -> SCResult<&'static str> {
+        self.clients.get_key_value(game_type)
+            .map(|(&key, _)| key)
+            .ok_or_else(|| format!("No plugin registered for game type {}", game_type).into())
+    }
+
+    /// Reads messages until the game type is known.
+    fn peek_game_type<R>(&self, xml_reader: &mut EventReader<R>) -> SCResult<&'static str> where R: Read {
+        // Read initial protocol element
+        loop {
+            match xml_reader.next()? {
+                XmlReadEvent::StartElement { name, .. } if name.local_name == "protocol" => break,
+                XmlReadEvent::EndDocument => return Err("Connection closed before the game began".into()),
+                _ => {}
+            }
+        }
+
+        loop {
+            let node = XmlNode::read_from(xml_reader)?;
+            match node.name() {
+                "joined" => {
+                    let joined = Joined::from_node(&node)?;
+                    if !joined.game_type.is_empty() {
+                        return self.registered_game_type(&joined.game_type);
+                    }
+                },
+                "room" => return self.clients.iter()
+                    .find(|(_, client)| client.accepts(&node))
+                    .map(|(&key, _)| key)
+                    .ok_or_else(|| format!("No plugin can handle the room message {}", node).into()),
+                "close" | "sc.protocol.responses.CloseConnection" => return Err("Connection closed before the game began".into()),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<R> Read for TeeReader<R> where R: Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.read.extend_from_slice(&buf[..count]);
+        Ok(count)
+    }
+}
@@ -0,0 +1,83 @@
+use log::{info, debug, warn, error};
+use crate::admin_client::SCAdminClient;
+use crate::plugin::{SCPlugin, HasTurn};
+use crate::protocol::{Room, Data, GameResult};
+use crate::util::SCResult;
+use crate::xml_node::FromXmlNode;
+
+/// A handler that is notified about the
+/// progress of an observed game.
+pub trait SCObserverDelegate {
+    /// The plugin defining which types are
+    /// representing various parts of the game.
+    type Plugin: SCPlugin;
+
+    /// Invoked whenever the game state updates.
+    fn on_update_state(&mut self, _state: &<Self::Plugin as SCPlugin>::GameState) {}
+
+    /// Invoked when the game ends.
+    fn on_game_end(&mut self, _result: GameResult<Self::Plugin>) {}
+}
+
+/// A client which watches a running room without
+/// playing, i.e. it never answers move requests.
+/// Since observing is an administrative action, it
+/// requires the server's administrator password.
+pub struct SCObserver<D> where D: SCObserverDelegate {
+    delegate: D
+}
+
+impl<D> SCObserver<D> where D: SCObserverDelegate {
+    /// Creates a new observer using the specified delegate.
+    pub fn new(delegate: D) -> Self {
+        Self { delegate }
+    }
+
+    /// Blocks the thread and observes the given room
+    /// via TCP until its game ends.
+    pub fn run(self, host: &str, port: u16, password: &str, room_id: &str) -> SCResult<()> {
+        let mut admin = SCAdminClient::connect(host, port, password)?;
+        admin.observe(room_id)?;
+        info!("Observing room {}", room_id);
+
+        self.run_with(admin, room_id)
+    }
+
+    /// Blocks the thread and handles the messages of a room,
+    /// which the admin client already observes, until its
+    /// game ends.
+    pub fn run_with(mut self, mut admin: SCAdminClient, room_id: &str) -> SCResult<()> {
+        loop {
+            let node = admin.receive()?;
+
+            match node.name() {
+                "room" => match <Room<D::Plugin>>::from_node(&node) {
+                    Ok(room) if room.room_id != room_id => debug!("Ignoring message from room {}", room.room_id),
+                    Ok(room) => match room.data {
+                        Data::Memento { state } => {
+                            info!("Got updated game state @ turn: {}", state.turn());
+                            self.delegate.on_update_state(&state);
+                        },
+                        Data::GameResult(result) => {
+                            info!("Got game result: {:?}", result);
+                            self.delegate.on_game_end(result);
+                            break;
+                        },
+                        Data::MoveRequest => debug!("Ignoring move request, since observers do not play"),
+                        data => debug!("Ignoring room data {:?}", data)
+                    },
+                    Err(e) => error!("Could not parse node as room: {:?}", e)
+                },
+
+                "close" | "sc.protocol.responses.CloseConnection" => {
+                    info!("Closing connection as requested by server...");
+                    break;
+                },
+
+                _ => warn!("Unrecognized message: <{}>", node.name())
+            }
+        }
+
+        Ok(())
+    }
+}
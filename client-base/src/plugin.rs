@@ -1,25 +1,31 @@
+use std::cmp::Ordering;
 use std::str::FromStr;
-use std::fmt::Debug;
-use crate::xml_node::{FromXmlNode, XmlNode};
+use std::fmt::{Debug, Display};
+use crate::xml_node::{FromXmlNode, XmlNode, XmlNodeBuilder};
 use crate::util::HasOpponent;
 use crate::error::SCError;
+use crate::util::SCResult;
 
 /// An "type family" trait that defines types
 /// which represent various parts of a game.
 pub trait SCPlugin: Debug {
-    type PlayerColor: Copy + Debug + Eq + HasOpponent + FromStr<Err=SCError>;
-    type Player: Clone + Debug + Eq + FromXmlNode;
-    type GameState: Clone + Debug + Eq + FromXmlNode + HasPlayerColor<PlayerColor=Self::PlayerColor> + HasTurn;
-    type Move: Clone + Debug + Eq + Into<XmlNode>;
+    type PlayerColor: Copy + Debug + Display + Eq + Send + Sync + HasOpponent + FromStr<Err=SCError>;
+    type Player: Clone + Debug + Eq + Send + Sync + FromXmlNode + Into<XmlNodeBuilder<'static>> + HasPlayerColor<PlayerColor=Self::PlayerColor>;
+    type GameState: Clone + Debug + Eq + Send + Sync + FromXmlNode + Into<XmlNode> + HasPlayerColor<PlayerColor=Self::PlayerColor> + HasTurn + HasRules<Move=Self::Move>;
+    type Move: Clone + Debug + Eq + Send + Sync + FromXmlNode + Into<XmlNode>;
     
     /// Fetches the 'gameType' used during
     /// the protocol handshake.
     fn protocol_game_type<'a>() -> &'a str;
+    
+    /// Fetches the colors in the order in which they are
+    /// assigned to joining players and scores are listed.
+    fn player_colors() -> Vec<Self::PlayerColor>;
 }
 
 /// Indicates that the value has an "associated" player color.
 /// The plugin-specific `GameState` should return the current player
-/// color when implementing this trait.
+/// color and the `Player` its own color when implementing this trait.
 pub trait HasPlayerColor {
     type PlayerColor;
 
@@ -32,3 +38,56 @@ pub trait HasTurn {
     /// Fetches the turn.
     fn turn(&self) -> u32;
 }
+
+/// Provides the rules of a game. The plugin-specific `GameState`
+/// implements this trait, which makes it possible to write
+/// move-selection logic (e.g. a minimax search) once and reuse
+/// it across different games.
+pub trait HasRules: HasPlayerColor {
+    type Move;
+
+    /// Fetches the legal moves for the player
+    /// whose turn it currently is.
+    fn possible_moves(&self) -> Vec<Self::Move>;
+
+    /// Checks whether the given move is legal
+    /// in the current state.
+    fn validate_move(&self, game_move: &Self::Move) -> SCResult<()>;
+
+    /// Applies the given move to the state.
+    fn perform_move(&mut self, game_move: Self::Move) -> SCResult<()>;
+
+    /// Infers the move leading from this state to the given
+    /// successor, e.g. to learn about the opponent's moves
+    /// from the server's states. By default, the legal moves
+    /// are searched, which plugins can replace by comparing
+    /// the states directly.
+    fn infer_move(&self, next: &Self) -> Option<Self::Move> where Self: Sized + Clone + Eq, Self::Move: Clone {
+        self.possible_moves().into_iter().find(|m| self.after_move(m.clone()).ok().as_ref() == Some(next))
+    }
+
+    /// Whether the game has ended.
+    fn is_game_over(&self) -> bool;
+
+    /// Computes the points of the given player.
+    fn points(&self, color: Self::PlayerColor) -> i32;
+
+    /// Fetches the state after the given move.
+    fn after_move(&self, game_move: Self::Move) -> SCResult<Self> where Self: Sized + Clone {
+        let mut state = self.clone();
+        state.perform_move(game_move)?;
+        Ok(state)
+    }
+
+    /// Determines the winner by comparing the points
+    /// of both players. A draw is represented as `None`.
+    fn winner(&self) -> Option<Self::PlayerColor> where Self::PlayerColor: Copy + HasOpponent {
+        let color = self.player_color();
+        let opponent = color.opponent();
+        match self.points(color).cmp(&self.points(opponent)) {
+            Ordering::Greater => Some(color),
+            Ordering::Less => Some(opponent),
+            Ordering::Equal => None
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use log::{debug, error};
+
+/// A computation that runs on a background thread while
+/// the opponent is thinking. Results that should be reused
+/// later can be shared with the delegate, e.g. through an
+/// `Arc<Mutex<...>>` holding a transposition table.
+pub type PonderJob = Box<dyn FnOnce(&CancellationToken) + Send>;
+
+/// Signals a background computation to stop. Jobs
+/// should check it regularly, since the client waits
+/// for them to finish before handling the next state.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>
+}
+
+impl CancellationToken {
+    /// Creates a new, uncancelled token.
+    pub fn new() -> Self { Self::default() }
+
+    /// Whether the computation should stop.
+    pub fn is_cancelled(&self) -> bool { self.cancelled.load(Ordering::Acquire) }
+
+    /// Requests the computation to stop.
+    pub fn cancel(&self) { self.cancelled.store(true, Ordering::Release) }
+}
+
+/// A running ponder job, which is cancelled when dropped.
+pub(crate) struct Pondering {
+    token: CancellationToken,
+    handle: Option<JoinHandle<()>>
+}
+
+impl Pondering {
+    /// Runs the given job on a background thread.
+    pub(crate) fn start(job: PonderJob) -> Self {
+        let token = CancellationToken::new();
+        let job_token = token.clone();
+        let handle = thread::spawn(move || job(&job_token));
+        debug!("Started pondering");
+        Self { token, handle: Some(handle) }
+    }
+
+    /// Cancels the job and waits for it to finish.
+    pub(crate) fn stop(mut self) {
+        self.token.cancel();
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                error!("Ponder job panicked");
+            }
+        }
+        debug!("Stopped pondering");
+    }
+}
+
+impl Drop for Pondering {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
@@ -0,0 +1,9 @@
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
+
+/// A message with which an administrator
+/// authenticates against the server.
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+pub struct Authenticate {
+    #[xml(rename = "passphrase")]
+    pub password: String
+}
@@ -10,7 +10,7 @@ use super::GameResult;
 /// and the player structure (`P`). These types
 /// are implemented independently of the base
 /// protocol for each year's game.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug)]
 pub enum Data<P> where P: SCPlugin {
     WelcomeMessage { color: P::PlayerColor },
     Memento { state: P::GameState },
@@ -20,16 +20,50 @@ pub enum Data<P> where P: SCPlugin {
     Error { message: String }
 }
 
+// Manual implementations avoid the `P: Clone` and `P: PartialEq`
+// bounds a derive would add, since plugins are only type families.
+
+impl<P> Clone for Data<P> where P: SCPlugin {
+    fn clone(&self) -> Self {
+        match self {
+            Self::WelcomeMessage { color } => Self::WelcomeMessage { color: *color },
+            Self::Memento { state } => Self::Memento { state: state.clone() },
+            Self::Move(game_move) => Self::Move(game_move.clone()),
+            Self::MoveRequest => Self::MoveRequest,
+            Self::GameResult(result) => Self::GameResult(result.clone()),
+            Self::Error { message } => Self::Error { message: message.clone() }
+        }
+    }
+}
+
+impl<P> PartialEq for Data<P> where P: SCPlugin {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::WelcomeMessage { color: a }, Self::WelcomeMessage { color: b }) => a == b,
+            (Self::Memento { state: a }, Self::Memento { state: b }) => a == b,
+            (Self::Move(a), Self::Move(b)) => a == b,
+            (Self::MoveRequest, Self::MoveRequest) => true,
+            (Self::GameResult(a), Self::GameResult(b)) => a == b,
+            (Self::Error { message: a }, Self::Error { message: b }) => a == b,
+            _ => false
+        }
+    }
+}
+
+impl<P> Eq for Data<P> where P: SCPlugin {}
+
 impl<P> FromXmlNode for Data<P> where P: SCPlugin {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
         let class = node.attribute("class")?;
         match class {
-            "welcomeMessage" => Ok(Self::WelcomeMessage { color: node.attribute("color")?.parse()? }),
+            "welcomeMessage" => Ok(Self::WelcomeMessage { color: node.parse_attribute("color")? }),
             "memento" => Ok(Self::Memento { state: P::GameState::from_node(node.child_by_name("state")?)? }),
             "sc.framework.plugins.protocol.MoveRequest" => Ok(Self::MoveRequest),
             "result" => Ok(Self::GameResult(GameResult::from_node(node)?)),
             "error" => Ok(Self::Error { message: node.attribute("message")?.to_owned() }),
-            _ => Err(format!("Unrecognized data class: {}", class).into())
+            // Move classes are game-specific, therefore the plugin decides
+            // whether it recognizes the data class
+            _ => Ok(Self::Move(P::Move::from_node(node)?))
         }
     }
 }
@@ -39,8 +73,23 @@ impl<P> TryFrom<Data<P>> for XmlNode where P: SCPlugin {
 
     fn try_from(data: Data<P>) -> SCResult<XmlNode> {
         match data {
+            Data::WelcomeMessage { color } => Ok(XmlNode::new("data")
+                .attribute("class", "welcomeMessage")
+                .attribute("color", color.to_string())
+                .build()),
+            Data::Memento { state } => Ok(XmlNode::new("data")
+                .attribute("class", "memento")
+                .child(state)
+                .build()),
             Data::Move(game_move) => Ok(game_move.into()),
-            _ => Err(format!("{:?} can currently not be serialized", data).into())
+            Data::MoveRequest => Ok(XmlNode::new("data")
+                .attribute("class", "sc.framework.plugins.protocol.MoveRequest")
+                .build()),
+            Data::GameResult(result) => Ok(result.into()),
+            Data::Error { message } => Ok(XmlNode::new("data")
+                .attribute("class", "error")
+                .attribute("message", message)
+                .build())
         }
     }
 }
@@ -1,15 +1,64 @@
-use crate::{plugin::SCPlugin, util::SCResult, xml_node::FromXmlNode, xml_node::XmlNode};
+use std::cmp::Ordering;
+use crate::{plugin::{HasPlayerColor, SCPlugin}, util::SCResult, xml_node::{FromXmlNode, XmlNode}};
 
 use super::{PlayerScore, ScoreDefinition};
 
 /// The final result of a game.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug)]
 pub struct GameResult<P> where P: SCPlugin {
     pub definition: ScoreDefinition,
     pub scores: Vec<PlayerScore>,
     pub winners: Vec<P::Player>
 }
 
+impl<P> GameResult<P> where P: SCPlugin {
+    /// Fetches the score of the player with the given
+    /// color, assuming that scores are listed in the
+    /// order of `SCPlugin::player_colors`.
+    pub fn score(&self, color: P::PlayerColor) -> Option<&PlayerScore> {
+        P::player_colors().iter().position(|&c| c == color).and_then(|i| self.scores.get(i))
+    }
+    
+    /// Pairs the player colors with their scores.
+    pub fn scores_by_color(&self) -> Vec<(P::PlayerColor, &PlayerScore)> {
+        P::player_colors().into_iter().zip(self.scores.iter()).collect()
+    }
+    
+    /// Checks whether the player with the given color has won.
+    pub fn is_winner(&self, color: P::PlayerColor) -> bool {
+        self.winners.iter().any(|w| w.player_color() == color)
+    }
+    
+    /// Checks whether the game ended without a winner.
+    pub fn is_draw(&self) -> bool {
+        self.winners.is_empty()
+    }
+    
+    /// Fetches the 1-based rank of the player with the given color.
+    /// Winners are ranked first, the remaining players by their
+    /// values relevant for ranking. Tied players share a rank.
+    pub fn rank(&self, color: P::PlayerColor) -> Option<usize> {
+        let key = |c: P::PlayerColor, score: &PlayerScore| (self.is_winner(c), score.ranking_values(&self.definition));
+        let own = key(color, self.score(color)?);
+        let better = self.scores_by_color().into_iter()
+            .filter(|&(c, score)| key(c, score).partial_cmp(&own) == Some(Ordering::Greater))
+            .count();
+        Some(better + 1)
+    }
+}
+
+impl<P> Clone for GameResult<P> where P: SCPlugin {
+    fn clone(&self) -> Self {
+        Self { definition: self.definition.clone(), scores: self.scores.clone(), winners: self.winners.clone() }
+    }
+}
+
+impl<P> PartialEq for GameResult<P> where P: SCPlugin {
+    fn eq(&self, other: &Self) -> bool {
+        self.definition == other.definition && self.scores == other.scores && self.winners == other.winners
+    }
+}
+
 impl<P> FromXmlNode for GameResult<P> where P: SCPlugin, P::Player: FromXmlNode {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
         Ok(Self {
@@ -19,3 +68,14 @@ impl<P> FromXmlNode for GameResult<P> where P: SCPlugin, P::Player: FromXmlNode
         })
     }
 }
+
+impl<P> From<GameResult<P>> for XmlNode where P: SCPlugin {
+    fn from(result: GameResult<P>) -> Self {
+        XmlNode::new("data")
+            .attribute("class", "result")
+            .child(result.definition)
+            .childs(result.scores.into_iter().map(XmlNode::from))
+            .childs(result.winners.into_iter().map(|w| w.into().name("winner").build()))
+            .build()
+    }
+}
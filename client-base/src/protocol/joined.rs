@@ -1,12 +1,12 @@
-use crate::{util::SCResult, xml_node::{FromXmlNode, XmlNode}};
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
 
 /// A message indicating that the client
 /// has joined a room with the specified id.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
 pub struct Joined {
-    pub room_id: String
-}
-
-impl FromXmlNode for Joined {
-    fn from_node(node: &XmlNode) -> SCResult<Self> { Ok(Self { room_id: node.attribute("roomId")?.to_owned() }) }
+    pub room_id: String,
+    /// The game type played in the room, which
+    /// is empty if the server does not report it.
+    #[xml(default)]
+    pub game_type: String
 }
@@ -1,12 +1,8 @@
-use crate::{util::SCResult, xml_node::{FromXmlNode, XmlNode}};
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
 
 /// A message indicating that the client
 /// has left a room with the specified id.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
 pub struct Left {
     pub room_id: String
 }
-
-impl FromXmlNode for Left {
-    fn from_node(node: &XmlNode) -> SCResult<Self> { Ok(Self { room_id: node.attribute("roomId")?.to_owned() }) }
-}
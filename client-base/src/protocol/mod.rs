@@ -1,24 +1,39 @@
 //! The data structures used by the XML protocol.
 
+mod authenticate;
 mod data;
 mod game_result;
 mod joined;
 mod left;
+mod observe;
+mod observed;
+mod pause;
 mod player_score;
+mod prepare;
+mod prepared;
 mod room;
 mod score_aggregation;
 mod score_cause;
 mod score_definition;
 mod score_fragment;
+mod slot_descriptor;
+mod step;
 
+pub use authenticate::*;
 pub use data::*;
 pub use game_result::*;
 pub use joined::*;
 pub use left::*;
-pub use data::*;
+pub use observe::*;
+pub use observed::*;
+pub use pause::*;
 pub use player_score::*;
+pub use prepare::*;
+pub use prepared::*;
 pub use room::*;
-pub use score_definition::*;
-pub use score_fragment::*;
 pub use score_aggregation::*;
 pub use score_cause::*;
+pub use score_definition::*;
+pub use score_fragment::*;
+pub use slot_descriptor::*;
+pub use step::*;
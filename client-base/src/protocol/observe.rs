@@ -0,0 +1,8 @@
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
+
+/// An administrative request to receive
+/// the messages of the specified room.
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+pub struct Observe {
+    pub room_id: String
+}
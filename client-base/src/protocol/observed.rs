@@ -0,0 +1,8 @@
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
+
+/// A message indicating that the client
+/// observes the room with the specified id.
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+pub struct Observed {
+    pub room_id: String
+}
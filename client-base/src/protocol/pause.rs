@@ -0,0 +1,9 @@
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
+
+/// An administrative request to pause
+/// or resume the game in a room.
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+pub struct Pause {
+    pub room_id: String,
+    pub pause: bool
+}
@@ -1,19 +1,27 @@
-use crate::{util::SCResult, xml_node::FromXmlNode, xml_node::XmlNode};
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
 
-use super::ScoreCause;
+use super::{ScoreCause, ScoreDefinition, ScoreFragment};
 
 /// The score of a game player.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, FromXmlNode, IntoXmlNode)]
+#[xml(name = "score")]
 pub struct PlayerScore {
     pub cause: ScoreCause,
-    pub reason: String
+    #[xml(default)]
+    pub reason: String,
+    /// The values in the order of the definition's fragments.
+    #[xml(childs_content, rename = "part")]
+    pub parts: Vec<f64>
 }
 
-impl FromXmlNode for PlayerScore {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
-        Ok(Self {
-            cause: node.attribute("cause")?.parse()?,
-            reason: node.attribute("reason").map(|s| s.to_owned()).unwrap_or_default()
-        })
+impl PlayerScore {
+    /// Pairs the values with the fragments they belong to.
+    pub fn values<'a>(&'a self, definition: &'a ScoreDefinition) -> impl Iterator<Item=(&'a ScoreFragment, f64)> + 'a {
+        definition.fragments.iter().zip(self.parts.iter().cloned())
+    }
+    
+    /// Fetches the values that are relevant for ranking.
+    pub fn ranking_values(&self, definition: &ScoreDefinition) -> Vec<f64> {
+        self.values(definition).filter(|(f, _)| f.relevant_for_ranking).map(|(_, v)| v).collect()
     }
 }
@@ -0,0 +1,16 @@
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
+
+use super::SlotDescriptor;
+
+/// An administrative request to prepare a room
+/// whose players join using reservation codes.
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+pub struct Prepare {
+    pub game_type: String,
+    /// Whether the game starts paused, in which
+    /// case it has to be resumed or stepped.
+    #[xml(default)]
+    pub pause: bool,
+    #[xml(childs, rename = "slot")]
+    pub slots: Vec<SlotDescriptor>
+}
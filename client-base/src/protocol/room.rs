@@ -5,12 +5,26 @@ use crate::{error::SCError, plugin::SCPlugin, util::SCResult, xml_node::FromXmlN
 use super::Data;
 
 /// A message in a room together with some data.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug)]
 pub struct Room<P> where P: SCPlugin {
     pub room_id: String,
     pub data: Data<P>
 }
 
+impl<P> Clone for Room<P> where P: SCPlugin {
+    fn clone(&self) -> Self {
+        Self { room_id: self.room_id.clone(), data: self.data.clone() }
+    }
+}
+
+impl<P> PartialEq for Room<P> where P: SCPlugin {
+    fn eq(&self, other: &Self) -> bool {
+        self.room_id == other.room_id && self.data == other.data
+    }
+}
+
+impl<P> Eq for Room<P> where P: SCPlugin {}
+
 impl<P> FromXmlNode for Room<P> where P: SCPlugin {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
         Ok(Self {
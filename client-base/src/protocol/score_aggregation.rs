@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 /// Determines how scores should be aggregated (e.g. summed up or averaged over).
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -7,6 +7,19 @@ pub enum ScoreAggregation {
     Average
 }
 
+impl ScoreAggregation {
+    /// Aggregates the values, e.g. of a fragment
+    /// over several games. Averages of no values are zero.
+    pub fn aggregate(&self, values: impl IntoIterator<Item=f64>) -> f64 {
+        let (sum, count) = values.into_iter().fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+        match self {
+            Self::Sum => sum,
+            Self::Average if count == 0 => 0.0,
+            Self::Average => sum / f64::from(count)
+        }
+    }
+}
+
 impl FromStr for ScoreAggregation {
     type Err = String;
 
@@ -18,3 +31,12 @@ impl FromStr for ScoreAggregation {
         }
     }
 }
+
+impl fmt::Display for ScoreAggregation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Sum => write!(f, "SUM"),
+            Self::Average => write!(f, "AVERAGE")
+        }
+    }
+}
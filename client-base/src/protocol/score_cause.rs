@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 /// Determines the cause of a game score.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -26,3 +26,16 @@ impl FromStr for ScoreCause {
         }
     }
 }
+
+impl fmt::Display for ScoreCause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Regular => write!(f, "REGULAR"),
+            Self::Left => write!(f, "LEFT"),
+            Self::RuleViolation => write!(f, "RULE_VIOLATION"),
+            Self::SoftTimeout => write!(f, "SOFT_TIMEOUT"),
+            Self::HardTimeout => write!(f, "HARD_TIMEOUT"),
+            Self::Unknown => write!(f, "UNKNOWN")
+        }
+    }
+}
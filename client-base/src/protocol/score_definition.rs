@@ -1,17 +1,22 @@
-use crate::{util::SCResult, xml_node::FromXmlNode, xml_node::XmlNode};
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
 
-use super::ScoreFragment;
+use super::{PlayerScore, ScoreFragment};
 
 /// The definition of a score.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+#[xml(name = "definition")]
 pub struct ScoreDefinition {
+    #[xml(childs, rename = "fragment")]
     pub fragments: Vec<ScoreFragment>
 }
 
-impl FromXmlNode for ScoreDefinition {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
-        Ok(Self {
-            fragments: node.childs_by_name("fragment").map(ScoreFragment::from_node).collect::<SCResult<_>>()?
-        })
+impl ScoreDefinition {
+    /// Aggregates the scores, e.g. of a player over several
+    /// games, yielding one value per fragment.
+    pub fn aggregate<'a>(&self, scores: impl IntoIterator<Item=&'a PlayerScore>) -> Vec<f64> {
+        let scores: Vec<_> = scores.into_iter().collect();
+        self.fragments.iter().enumerate()
+            .map(|(i, fragment)| fragment.aggregation.aggregate(scores.iter().filter_map(|s| s.parts.get(i).cloned())))
+            .collect()
     }
 }
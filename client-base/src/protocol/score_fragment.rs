@@ -1,21 +1,14 @@
-use crate::{util::SCResult, xml_node::{FromXmlNode, XmlNode}};
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
 
 use super::ScoreAggregation;
 
 /// A single score fragment.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+#[xml(name = "fragment")]
 pub struct ScoreFragment {
     pub name: String,
+    #[xml(child_content)]
     pub aggregation: ScoreAggregation,
+    #[xml(child_content)]
     pub relevant_for_ranking: bool
 }
-
-impl FromXmlNode for ScoreFragment {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
-        Ok(Self {
-            name: node.attribute("name")?.to_owned(),
-            aggregation: node.child_by_name("aggregation")?.content().parse()?,
-            relevant_for_ranking: node.child_by_name("relevantForRanking")?.content().parse()?
-        })
-    }
-}
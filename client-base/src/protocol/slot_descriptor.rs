@@ -0,0 +1,35 @@
+use crate::{util::SCResult, xml_node::{FromXmlNode, XmlNode}};
+
+/// Describes a player's slot in a prepared game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotDescriptor {
+    pub display_name: String,
+    pub can_timeout: bool,
+    pub reserved: bool
+}
+
+impl Default for SlotDescriptor {
+    fn default() -> Self {
+        Self { display_name: String::new(), can_timeout: true, reserved: true }
+    }
+}
+
+impl FromXmlNode for SlotDescriptor {
+    fn from_node(node: &XmlNode) -> SCResult<Self> {
+        Ok(Self {
+            display_name: node.attribute("displayName").map(|s| s.to_owned()).unwrap_or_default(),
+            can_timeout: node.attribute("canTimeout").map_or(Ok(true), |_| node.parse_attribute("canTimeout"))?,
+            reserved: node.attribute("reserved").map_or(Ok(true), |_| node.parse_attribute("reserved"))?
+        })
+    }
+}
+
+impl From<SlotDescriptor> for XmlNode {
+    fn from(slot: SlotDescriptor) -> Self {
+        XmlNode::new("slot")
+            .attribute("displayName", slot.display_name)
+            .attribute("canTimeout", slot.can_timeout.to_string())
+            .attribute("reserved", slot.reserved.to_string())
+            .build()
+    }
+}
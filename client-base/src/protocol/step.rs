@@ -0,0 +1,8 @@
+use crate::xml_node::{FromXmlNode, IntoXmlNode};
+
+/// An administrative request to let a paused
+/// game in a room advance by a single move.
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+pub struct Step {
+    pub room_id: String
+}
@@ -0,0 +1,118 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use log::warn;
+use xml::reader::{EventReader, XmlEvent as XmlReadEvent};
+use xml::writer::{EmitterConfig, EventWriter, XmlEvent as XmlWriteEvent};
+use crate::util::SCResult;
+use crate::xml_node::XmlNode;
+
+/// The direction in which a message was exchanged.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    Received,
+    Sent
+}
+
+/// A message from a recorded session.
+#[derive(Debug)]
+pub struct RecordedMessage {
+    pub direction: Direction,
+    pub node: XmlNode
+}
+
+/// Writes every exchanged message to a log file,
+/// from which the session can be replayed later.
+/// Each message is flushed immediately, so the log
+/// remains usable even if the client crashes.
+pub struct Recorder {
+    writer: EventWriter<BufWriter<File>>
+}
+
+impl Direction {
+    /// Fetches the tag name wrapping messages
+    /// of this direction in a recording.
+    fn tag_name(self) -> &'static str {
+        match self {
+            Self::Received => "received",
+            Self::Sent => "sent"
+        }
+    }
+}
+
+impl Recorder {
+    /// Creates a new recording at the given path,
+    /// replacing any existing file.
+    pub fn create(path: impl AsRef<Path>) -> SCResult<Self> {
+        let mut emitter_config = EmitterConfig::new();
+        emitter_config.write_document_declaration = false;
+        emitter_config.perform_indent = true;
+
+        let mut writer = emitter_config.create_writer(BufWriter::new(File::create(path)?));
+        writer.inner_mut().write_all("<recording>\n".as_bytes())?;
+        writer.inner_mut().flush()?;
+        Ok(Self { writer })
+    }
+
+    /// Appends a message to the recording.
+    pub fn record(&mut self, direction: Direction, node: &XmlNode) -> SCResult<()> {
+        self.writer.write(XmlWriteEvent::start_element(direction.tag_name()))?;
+        node.write_to(&mut self.writer)?;
+        self.writer.write(XmlWriteEvent::end_element())?;
+        self.writer.inner_mut().write_all("\n".as_bytes())?;
+        self.writer.inner_mut().flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let inner = self.writer.inner_mut();
+        if inner.write_all("</recording>\n".as_bytes()).and_then(|_| inner.flush()).is_err() {
+            warn!("Could not terminate recording");
+        }
+    }
+}
+
+/// Reads the messages from a recording. Recordings
+/// that end abruptly, e.g. because the client was
+/// killed, are read up to the last complete message.
+pub fn read_recording(path: impl AsRef<Path>) -> SCResult<Vec<RecordedMessage>> {
+    let mut reader = EventReader::new(BufReader::new(File::open(path)?));
+    let mut messages = Vec::new();
+
+    loop {
+        match reader.next()? {
+            XmlReadEvent::StartElement { name, .. } if name.local_name == "recording" => break,
+            XmlReadEvent::EndDocument => return Err("Reached end of document while waiting for <recording>".into()),
+            _ => ()
+        }
+    }
+
+    loop {
+        let direction = match reader.next() {
+            Ok(XmlReadEvent::StartElement { name, .. }) => match name.local_name.as_str() {
+                "received" => Direction::Received,
+                "sent" => Direction::Sent,
+                other => return Err(format!("Unrecognized recording entry <{}>", other).into())
+            },
+            Ok(XmlReadEvent::EndElement { name }) if name.local_name == "recording" => break,
+            Ok(XmlReadEvent::EndDocument) => break,
+            Ok(_) => continue,
+            Err(e) => {
+                warn!("Recording ended unexpectedly: {}", e);
+                break;
+            }
+        };
+
+        match XmlNode::read_from(&mut reader) {
+            Ok(node) => messages.push(RecordedMessage { direction, node }),
+            Err(e) => {
+                warn!("Recording ended unexpectedly: {:?}", e);
+                break;
+            }
+        }
+    }
+
+    Ok(messages)
+}
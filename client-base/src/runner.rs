@@ -0,0 +1,193 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::{Mutex, OnceLock};
+use log::{error, LevelFilter, Log, Metadata, Record};
+use crate::client::{DebugMode, SCClient, SCClientDelegate};
+use crate::telemetry::JsonLinesSink;
+use crate::util::SCResult;
+
+const USAGE: &str = "Usage: <client> [--host <host>] [--port <port>] [--reservation <code>] [--games <count>] [-v | -vv] [--quiet] [--log-file <path>] [--debug-reader] [--debug-writer] [--record <path>] [--telemetry <path>]";
+
+/// The exit code used when the client ran successfully.
+pub const EXIT_SUCCESS: i32 = 0;
+/// The exit code used when the client failed.
+pub const EXIT_FAILURE: i32 = 1;
+/// The exit code used when the arguments are invalid.
+pub const EXIT_USAGE: i32 = 2;
+
+/// The standard command-line arguments of
+/// a Software Challenge client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunnerArgs {
+    pub host: String,
+    pub port: u16,
+    pub reservation: Option<String>,
+    pub games: usize,
+    pub log_level: LevelFilter,
+    pub log_file: Option<PathBuf>,
+    pub debug_reader: bool,
+    pub debug_writer: bool,
+    pub recording: Option<PathBuf>,
+    pub telemetry: Option<PathBuf>
+}
+
+impl Default for RunnerArgs {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_owned(),
+            port: 13050,
+            reservation: None,
+            games: 1,
+            log_level: LevelFilter::Info,
+            log_file: None,
+            debug_reader: false,
+            debug_writer: false,
+            recording: None,
+            telemetry: None
+        }
+    }
+}
+
+impl RunnerArgs {
+    /// Parses the arguments, excluding the program name.
+    /// Both the long and the short options used by the
+    /// official clients (`-h`, `-p`, `-r`) are accepted.
+    pub fn parse(args: impl IntoIterator<Item=String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("Missing value for {}", arg));
+            match arg.as_str() {
+                "-h" | "--host" => parsed.host = value()?,
+                "-p" | "--port" => {
+                    let port = value()?;
+                    parsed.port = port.parse().map_err(|_| format!("Invalid port {}", port))?;
+                },
+                "-r" | "--reservation" => parsed.reservation = Some(value()?),
+                "--games" => {
+                    let games = value()?;
+                    parsed.games = games.parse().map_err(|_| format!("Invalid game count {}", games))?;
+                },
+                "--log-file" => parsed.log_file = Some(value()?.into()),
+                "--record" => parsed.recording = Some(value()?.into()),
+                "--telemetry" => parsed.telemetry = Some(value()?.into()),
+                "-v" | "--verbose" => parsed.log_level = LevelFilter::Debug,
+                "-vv" => parsed.log_level = LevelFilter::Trace,
+                "-q" | "--quiet" => parsed.log_level = LevelFilter::Warn,
+                "--debug-reader" => parsed.debug_reader = true,
+                "--debug-writer" => parsed.debug_writer = true,
+                _ => return Err(format!("Unrecognized argument {}", arg))
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Fetches the debug mode configured by the arguments.
+    pub fn debug_mode(&self) -> DebugMode {
+        DebugMode {
+            debug_reader: self.debug_reader,
+            debug_writer: self.debug_writer,
+            recording: self.recording.clone()
+        }
+    }
+}
+
+/// The logger installed by the first run, which has to
+/// live as long as the process.
+static LOGGER: OnceLock<RunnerLogger> = OnceLock::new();
+
+/// A minimal logger writing to stderr or a file.
+struct RunnerLogger {
+    level: LevelFilter,
+    file: Option<Mutex<File>>
+}
+
+impl Log for RunnerLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool { metadata.level() <= self.level }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let line = format!("[{} {}] {}\n", record.level(), record.target(), record.args());
+            let _ = match self.file {
+                Some(ref file) => file.lock().unwrap().write_all(line.as_bytes()),
+                // Stdout is reserved for the protocol in debug mode
+                None => io::stderr().write_all(line.as_bytes())
+            };
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(ref file) = self.file {
+            let _ = file.lock().unwrap().flush();
+        }
+    }
+}
+
+/// Installs a logger as configured by the arguments. Does
+/// nothing if a logger has already been installed.
+fn init_logger(args: &RunnerArgs) -> SCResult<()> {
+    if LOGGER.get().is_some() {
+        return Ok(());
+    }
+    let file = match args.log_file {
+        Some(ref path) => Some(Mutex::new(File::create(path)?)),
+        None => None
+    };
+    let logger = LOGGER.get_or_init(|| RunnerLogger { level: args.log_level, file });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(args.log_level);
+    }
+    Ok(())
+}
+
+/// Runs a client with the given arguments and a delegate
+/// created by the factory. Returns the exit code.
+pub fn run<D, F>(args: impl IntoIterator<Item=String>, delegate_factory: F) -> i32 where D: SCClientDelegate, F: FnOnce() -> D {
+    let args = match RunnerArgs::parse(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    if let Err(e) = init_logger(&args) {
+        eprintln!("Could not initialize logger: {:?}", e);
+        return EXIT_FAILURE;
+    }
+
+    let mut client = SCClient::new(delegate_factory(), args.debug_mode()).with_game_count(args.games);
+    if let Some(ref path) = args.telemetry {
+        match JsonLinesSink::create(path) {
+            Ok(sink) => client = client.with_telemetry(sink),
+            Err(e) => {
+                error!("Could not create telemetry log: {:?}", e);
+                return EXIT_FAILURE;
+            }
+        }
+    }
+    match client.run(&args.host, args.port, args.reservation.as_deref()) {
+        Ok(_) => EXIT_SUCCESS,
+        Err(e) => {
+            error!("Client failed: {:?}", e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Runs a client with the process' arguments and exits
+/// the process afterwards. This is intended to be the
+/// only call in a client's `main`:
+///
+/// ```ignore
+/// fn main() {
+///     socha_client_base::runner::run_and_exit(|| MyDelegate::default());
+/// }
+/// ```
+pub fn run_and_exit<D, F>(delegate_factory: F) -> ! where D: SCClientDelegate, F: FnOnce() -> D {
+    process::exit(run(std::env::args().skip(1), delegate_factory))
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+use crate::history::GameHistory;
+use crate::plugin::SCPlugin;
+use crate::protocol::{GameResult, PlayerScore};
+
+/// Statistics about one of the client's turns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnStats {
+    pub turn: u32,
+    /// The time until the move was sent.
+    pub duration: Duration,
+    /// Whether the deadline was reached and the
+    /// latest candidate was sent instead.
+    pub timed_out: bool,
+    /// Whether a fallback move was sent, since the
+    /// delegate panicked or the watchdog fired.
+    pub fallback: bool
+}
+
+/// The outcome of a game played by the client.
+#[derive(Debug)]
+pub struct GameSummary<P> where P: SCPlugin {
+    pub room_id: String,
+    pub my_color: Option<P::PlayerColor>,
+    pub result: GameResult<P>,
+    pub final_state: Option<P::GameState>,
+    pub turns: Vec<TurnStats>,
+    pub history: GameHistory<P>
+}
+
+impl<P> GameSummary<P> where P: SCPlugin {
+    /// Fetches the client's own score.
+    pub fn my_score(&self) -> Option<&PlayerScore> {
+        self.my_color.and_then(|c| self.result.score(c))
+    }
+
+    /// Checks whether the client has won the game.
+    pub fn did_win(&self) -> bool {
+        self.my_color.is_some_and(|c| self.result.is_winner(c))
+    }
+
+    /// Fetches the client's 1-based rank.
+    pub fn rank(&self) -> Option<usize> {
+        self.my_color.and_then(|c| self.result.rank(c))
+    }
+
+    /// Fetches the total time spent on moves.
+    pub fn total_move_time(&self) -> Duration {
+        self.turns.iter().map(|t| t.duration).sum()
+    }
+
+    /// Fetches the number of turns in which
+    /// the deadline was reached.
+    pub fn timeouts(&self) -> usize {
+        self.turns.iter().filter(|t| t.timed_out).count()
+    }
+}
+
+/// Aggregates the client's own scores over several games,
+/// yielding one value per fragment of the first game's
+/// score definition.
+pub fn aggregate_scores<P>(summaries: &[GameSummary<P>]) -> Vec<f64> where P: SCPlugin {
+    match summaries.first() {
+        Some(first) => first.result.definition.aggregate(summaries.iter().filter_map(GameSummary::my_score)),
+        None => Vec::new()
+    }
+}
+
+impl<P> Clone for GameSummary<P> where P: SCPlugin {
+    fn clone(&self) -> Self {
+        Self {
+            room_id: self.room_id.clone(),
+            my_color: self.my_color,
+            result: self.result.clone(),
+            final_state: self.final_state.clone(),
+            turns: self.turns.clone(),
+            history: self.history.clone()
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt::{self, Write as _};
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+use log::{warn, error};
+use crate::dispatch::panic_message;
+use crate::plugin::{SCPlugin, HasRules};
+use crate::summary::TurnStats;
+use crate::util::SCResult;
+use crate::xml_node::XmlNode;
+
+/// A record of one of the client's turns.
+pub struct TurnRecord<P> where P: SCPlugin {
+    pub room_id: String,
+    /// The color the move was sent for.
+    pub color: P::PlayerColor,
+    pub stats: TurnStats,
+    /// The state in which the move was requested.
+    pub state: P::GameState,
+    pub sent_move: P::Move,
+    /// The metrics supplied by the delegate.
+    pub metrics: Vec<(String, f64)>
+}
+
+/// A destination for turn records. Sinks run
+/// on a background thread of the client.
+pub trait TelemetrySink<P>: Send where P: SCPlugin {
+    /// Records a turn.
+    fn record(&mut self, record: &TurnRecord<P>) -> SCResult<()>;
+}
+
+/// Writes each turn record as a line of JSON.
+/// Lines are flushed immediately, so the log
+/// remains usable even if the client crashes.
+pub struct JsonLinesSink<W> where W: Write {
+    writer: LineWriter<W>
+}
+
+/// Passes the turn records to a sink on a background
+/// thread, which keeps expensive sinks from delaying
+/// the client's reads.
+pub(crate) struct TelemetryWorker<P> where P: SCPlugin {
+    records: Sender<TurnRecord<P>>,
+    thread: JoinHandle<Box<dyn TelemetrySink<P>>>
+}
+
+impl<P> TurnRecord<P> where P: SCPlugin {
+    /// Counts the legal moves in the requested state. This
+    /// can be expensive, which is why it is left to the
+    /// sinks that need it.
+    pub fn legal_moves(&self) -> usize {
+        self.state.possible_moves().len()
+    }
+
+    /// Serializes the record to a single line of JSON.
+    pub fn to_json(&self) -> String {
+        let sent_move: XmlNode = self.sent_move.clone().into();
+        let mut json = String::from("{");
+        let _ = write!(json, "\"room_id\":{},", json_string(&self.room_id));
+        let _ = write!(json, "\"turn\":{},", self.stats.turn);
+        let _ = write!(json, "\"color\":{},", json_string(&self.color.to_string()));
+        let _ = write!(json, "\"elapsed_ms\":{},", json_number(self.stats.duration.as_secs_f64() * 1000.0));
+        let _ = write!(json, "\"timed_out\":{},", self.stats.timed_out);
+        let _ = write!(json, "\"fallback\":{},", self.stats.fallback);
+        let _ = write!(json, "\"legal_moves\":{},", self.legal_moves());
+        let _ = write!(json, "\"move\":{},", json_string(&sent_move.to_string()));
+        let metrics: Vec<_> = self.metrics.iter().map(|(k, v)| format!("{}:{}", json_string(k), json_number(*v))).collect();
+        let _ = write!(json, "\"metrics\":{{{}}}}}", metrics.join(","));
+        json
+    }
+}
+
+impl<P> Clone for TurnRecord<P> where P: SCPlugin {
+    fn clone(&self) -> Self {
+        Self {
+            room_id: self.room_id.clone(),
+            color: self.color,
+            stats: self.stats.clone(),
+            state: self.state.clone(),
+            sent_move: self.sent_move.clone(),
+            metrics: self.metrics.clone()
+        }
+    }
+}
+
+impl<P> fmt::Debug for TurnRecord<P> where P: SCPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TurnRecord")
+            .field("room_id", &self.room_id)
+            .field("color", &self.color)
+            .field("stats", &self.stats)
+            .field("sent_move", &self.sent_move)
+            .field("metrics", &self.metrics)
+            .finish()
+    }
+}
+
+impl JsonLinesSink<File> {
+    /// Creates a new log at the given path,
+    /// replacing any existing file.
+    pub fn create(path: impl AsRef<Path>) -> SCResult<Self> {
+        Ok(Self::new(File::create(path)?))
+    }
+}
+
+impl<W> JsonLinesSink<W> where W: Write {
+    /// Creates a sink writing to the given writer.
+    pub fn new(writer: W) -> Self {
+        Self { writer: LineWriter::new(writer) }
+    }
+
+    /// Fetches the underlying writer.
+    pub fn into_inner(self) -> SCResult<W> {
+        self.writer.into_inner().map_err(|e| e.into_error().into())
+    }
+}
+
+impl<P, W> TelemetrySink<P> for JsonLinesSink<W> where P: SCPlugin, W: Write + Send {
+    fn record(&mut self, record: &TurnRecord<P>) -> SCResult<()> {
+        writeln!(self.writer, "{}", record.to_json())?;
+        Ok(())
+    }
+}
+
+impl<P> TelemetryWorker<P> where P: SCPlugin {
+    /// Queues a record for the sink.
+    pub(crate) fn record(&self, record: TurnRecord<P>) {
+        if self.records.send(record).is_err() {
+            warn!("Could not record telemetry, since the sink has failed");
+        }
+    }
+}
+
+impl<P> TelemetryWorker<P> where P: SCPlugin + 'static {
+    /// Starts passing records to the given sink.
+    pub(crate) fn start(mut sink: Box<dyn TelemetrySink<P>>) -> Self {
+        let (records, receiver) = mpsc::channel::<TurnRecord<P>>();
+        let thread = thread::spawn(move || {
+            for record in receiver {
+                if let Err(e) = sink.record(&record) {
+                    warn!("Could not record telemetry: {:?}", e);
+                }
+            }
+            sink
+        });
+        Self { records, thread }
+    }
+
+    /// Waits until the queued records have been passed to the
+    /// sink and returns it. Returns `None` if the sink panicked.
+    pub(crate) fn finish(self) -> Option<Box<dyn TelemetrySink<P>>> {
+        drop(self.records);
+        match self.thread.join() {
+            Ok(sink) => Some(sink),
+            Err(payload) => {
+                error!("Telemetry sink panicked, disabling telemetry: {}", panic_message(&payload));
+                None
+            }
+        }
+    }
+}
+
+/// Encodes a string as a JSON string literal.
+fn json_string(raw: &str) -> String {
+    let mut encoded = String::from("\"");
+    for c in raw.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            '\r' => encoded.push_str("\\r"),
+            '\t' => encoded.push_str("\\t"),
+            c if (c as u32) < 0x20 => { let _ = write!(encoded, "\\u{:04x}", c as u32); },
+            c => encoded.push(c)
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+/// Encodes a number as JSON, which has
+/// no representation for non-finite values.
+fn json_number(value: f64) -> String {
+    if value.is_finite() { value.to_string() } else { "null".to_owned() }
+}
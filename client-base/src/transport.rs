@@ -0,0 +1,137 @@
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::process::{Child, ChildStdin, ChildStdout};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use crate::util::SCResult;
+
+/// A bidirectional byte stream over which
+/// the client exchanges XML messages with
+/// the server.
+pub trait Transport {
+    type Reader: Read;
+    type Writer: Write + Send;
+
+    /// Splits the transport into a reading
+    /// and a writing half.
+    fn split(self) -> SCResult<(Self::Reader, Self::Writer)>;
+}
+
+impl Transport for TcpStream {
+    type Reader = BufReader<TcpStream>;
+    type Writer = BufWriter<TcpStream>;
+
+    fn split(self) -> SCResult<(Self::Reader, Self::Writer)> {
+        Ok((BufReader::new(self.try_clone()?), BufWriter::new(self)))
+    }
+}
+
+#[cfg(unix)]
+impl Transport for UnixStream {
+    type Reader = BufReader<UnixStream>;
+    type Writer = BufWriter<UnixStream>;
+
+    fn split(self) -> SCResult<(Self::Reader, Self::Writer)> {
+        Ok((BufReader::new(self.try_clone()?), BufWriter::new(self)))
+    }
+}
+
+/// Talks to a child process over its stdio, which
+/// therefore has to be spawned with piped stdin/stdout.
+impl Transport for Child {
+    type Reader = BufReader<ChildStdout>;
+    type Writer = ChildStdin;
+
+    fn split(mut self) -> SCResult<(Self::Reader, Self::Writer)> {
+        let stdout = self.stdout.take().ok_or("The child's stdout is not piped")?;
+        let stdin = self.stdin.take().ok_or("The child's stdin is not piped")?;
+        Ok((BufReader::new(stdout), stdin))
+    }
+}
+
+/// Combines an arbitrary reader and writer, e.g.
+/// a recorded file and `io::sink()` for replays.
+impl<R, W> Transport for (R, W) where R: Read, W: Write + Send {
+    type Reader = R;
+    type Writer = W;
+
+    fn split(self) -> SCResult<(R, W)> { Ok(self) }
+}
+
+/// The reading end of an in-memory pipe.
+pub struct PipeReader {
+    receiver: Receiver<Vec<u8>>,
+    buffer: Vec<u8>,
+    position: usize
+}
+
+/// The writing end of an in-memory pipe.
+pub struct PipeWriter {
+    sender: Sender<Vec<u8>>
+}
+
+/// Creates an in-memory pipe. The reader reaches
+/// the end of the stream once the writer is dropped.
+pub fn pipe() -> (PipeWriter, PipeReader) {
+    let (sender, receiver) = channel();
+    (PipeWriter { sender }, PipeReader { receiver, buffer: Vec::new(), position: 0 })
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position >= self.buffer.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.buffer = chunk;
+                    self.position = 0;
+                },
+                Err(_) => return Ok(0)
+            }
+        }
+        let count = buf.len().min(self.buffer.len() - self.position);
+        buf[..count].copy_from_slice(&self.buffer[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !buf.is_empty() {
+            self.sender.send(buf.to_vec()).map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "The pipe's reader has been dropped"))?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+/// One end of an in-memory connection, which
+/// lets tests drive a full protocol exchange
+/// without opening sockets.
+pub struct MemoryTransport {
+    reader: PipeReader,
+    writer: PipeWriter
+}
+
+impl MemoryTransport {
+    /// Creates two connected ends, one of which
+    /// is usually handed to the client while the
+    /// other one plays the server's part.
+    pub fn pair() -> (Self, Self) {
+        let (client_writer, server_reader) = pipe();
+        let (server_writer, client_reader) = pipe();
+        (
+            Self { reader: client_reader, writer: client_writer },
+            Self { reader: server_reader, writer: server_writer }
+        )
+    }
+}
+
+impl Transport for MemoryTransport {
+    type Reader = PipeReader;
+    type Writer = PipeWriter;
+
+    fn split(self) -> SCResult<(PipeReader, PipeWriter)> { Ok((self.reader, self.writer)) }
+}
@@ -1,7 +1,7 @@
 use std::collections::{HashMap, VecDeque};
 use std::convert::TryInto;
 use std::fmt;
-use std::str;
+use std::str::{self, FromStr};
 use std::io::{Read, Write, Cursor};
 use xml::reader::{EventReader, XmlEvent as XmlReadEvent};
 use xml::writer::{EventWriter, EmitterConfig, XmlEvent as XmlWriteEvent};
@@ -9,11 +9,14 @@ use log::{warn, error};
 use crate::util::SCResult;
 use crate::error::SCError;
 
+pub use socha_xml_derive::{FromXmlNode, IntoXmlNode};
+
 /// A deserialized, in-memory tree-representation
 /// of an XML node.
 #[derive(Debug, Default)]
 pub struct XmlNode {
     name: String,
+    path: String,
     content: String,
     attributes: HashMap<String, String>,
     childs: Vec<XmlNode>
@@ -35,7 +38,8 @@ pub trait FromXmlNode where Self: Sized {
 
 impl XmlNode {
     /// Creates a new XML node builder.
-    pub fn new(name: &str) -> XmlNodeBuilder {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(name: &str) -> XmlNodeBuilder<'_> {
         XmlNodeBuilder::new(name)
     }
 
@@ -43,19 +47,34 @@ impl XmlNode {
     /// from the given XML event reader.
     pub fn read_from<R>(reader: &mut EventReader<R>) -> SCResult<XmlNode> where R: Read {
         let mut node_stack = VecDeque::<XmlNode>::new();
+        // The number of children with each name read so far,
+        // tracked for each node on the stack
+        let mut child_counts = VecDeque::<HashMap<String, usize>>::new();
         
         loop {
             match reader.next() {
                 Ok(XmlReadEvent::StartElement { name, attributes, .. }) => {
+                    let path = match (node_stack.back(), child_counts.back_mut()) {
+                        (Some(parent), Some(counts)) => {
+                            let count = counts.entry(name.local_name.clone()).or_insert(0);
+                            let path = format!("{}/{}[{}]", parent.path, name.local_name, count);
+                            *count += 1;
+                            path
+                        },
+                        _ => name.local_name.clone()
+                    };
                     let node = XmlNode {
                         name: name.local_name,
+                        path,
                         content: String::new(),
                         attributes: attributes.iter().cloned().map(|attr| (attr.name.local_name, attr.value)).collect(),
                         childs: Vec::new()
                     };
                     node_stack.push_back(node);
+                    child_counts.push_back(HashMap::new());
                 },
                 Ok(XmlReadEvent::EndElement { name }) => {
+                    child_counts.pop_back();
                     if let Some(node) = node_stack.pop_back() {
                         if let Some(mut parent) = node_stack.pop_back() {
                             parent.childs.push(node);
@@ -74,6 +93,7 @@ impl XmlNode {
                         warn!("Found characters {} outside of any node", content);
                     }
                 },
+                Ok(XmlReadEvent::EndDocument) => return Err("Reached end of document while reading node".into()),
                 Err(e) => return Err(e.into()),
                 _ => ()
             }
@@ -105,19 +125,66 @@ impl XmlNode {
         self.name.as_str()
     }
     
+    /// Changes the tag name of a built node.
+    pub fn renamed(mut self, name: &str) -> Self {
+        self.name = name.to_owned();
+        self.path = name.to_owned();
+        self
+    }
+    
+    /// Fetches the node's path within the message it was
+    /// read from, e.g. `room/data[0]/state[0]/board[0]/field[17]`,
+    /// where each element is indexed among its parent's children
+    /// of the same name, starting at 0. Built nodes only use
+    /// their tag name.
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+    
     /// Fetches the node's textual contents.
     pub fn content(&self) -> &str {
         self.content.as_str()
     }
     
+    /// Parses the node's textual contents.
+    pub fn parse_content<T>(&self) -> SCResult<T> where T: FromStr, T::Err: fmt::Display {
+        self.parse_value(&self.content)
+    }
+    
     /// Fetches an attribute's value by key.
     pub fn attribute(&self, key: &str) -> SCResult<&str> {
-        self.attributes.get(key).map(|s| s.as_str()).ok_or_else(|| format!("No attribute with key '{}' found in <{}>!", key, self.name).into())
+        self.attributes.get(key).map(|s| s.as_str()).ok_or_else(|| SCError::MissingAttribute { path: self.path.clone(), key: key.to_owned() })
+    }
+    
+    /// Parses an attribute's value by key.
+    pub fn parse_attribute<T>(&self, key: &str) -> SCResult<T> where T: FromStr, T::Err: fmt::Display {
+        self.parse_value(self.attribute(key)?)
+    }
+    
+    /// Parses a value found in this node.
+    fn parse_value<T>(&self, value: &str) -> SCResult<T> where T: FromStr, T::Err: fmt::Display {
+        value.parse().map_err(|e: T::Err| SCError::InvalidValue { path: self.path.clone(), value: value.to_owned(), message: e.to_string() })
+    }
+    
+    /// Creates an error indicating that the
+    /// node has an unrecognized data class.
+    pub fn unknown_class(&self, class: &str) -> SCError {
+        SCError::UnknownDataClass { path: self.path.clone(), class: class.to_owned() }
+    }
+    
+    /// Fetches the node's attributes.
+    pub fn attributes(&self) -> &HashMap<String, String> {
+        &self.attributes
+    }
+    
+    /// Fetches the node's child elements.
+    pub fn childs(&self) -> &[XmlNode] {
+        &self.childs
     }
     
     /// Finds the first child element with the provided tag name.
     pub fn child_by_name<'a, 'n: 'a>(&'a self, name: &'n str) -> SCResult<&'a XmlNode> {
-        self.childs_by_name(name).next().ok_or_else(|| format!("No <{}> found in <{}>!", name, self.name).into())
+        self.childs_by_name(name).next().ok_or_else(|| SCError::MissingChild { path: self.path.clone(), name: name.to_owned() })
     }
     
     /// Fetches a list of all child elements matching the provided tag name.
@@ -142,7 +209,7 @@ impl<'a> XmlNodeBuilder<'a> {
     /// Creates a new XML node builder with the
     /// specified tag name.
     pub fn new(name: &'a str) -> Self {
-        Self { name: name, content: "", attributes: HashMap::new(), childs: Vec::new() }
+        Self { name, content: "", attributes: HashMap::new(), childs: Vec::new() }
     }
     
     /// Sets the tag name of the XML node.
@@ -191,6 +258,7 @@ impl<'a> XmlNodeBuilder<'a> {
     pub fn build(self) -> XmlNode {
         XmlNode {
             name: self.name.to_owned(),
+            path: self.name.to_owned(),
             content: self.content.to_owned(),
             attributes: self.attributes,
             childs: self.childs
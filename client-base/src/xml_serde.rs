@@ -0,0 +1,615 @@
+//! Conversions between XML nodes and types implementing
+//! serde's `Serialize` and `Deserialize`, which lets the
+//! same types be stored in other formats, e.g. as JSON.
+//!
+//! Scalars are mapped to attributes, while structs, maps
+//! and sequences are mapped to child elements named after
+//! the field (repeated for every element of a sequence).
+//! A field named `$value` maps to the element's contents.
+//! Enum variants carrying data are wrapped in an element
+//! named after the variant.
+//!
+//! Since empty sequences produce no child elements,
+//! sequence fields that may be empty should be marked
+//! with `#[serde(default)]`.
+
+use std::vec;
+use serde::de::{self, value::BorrowedStrDeserializer, Deserialize, Deserializer, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+use crate::error::SCError;
+use crate::util::SCResult;
+use crate::xml_node::XmlNode;
+
+/// The field name mapping to an element's contents.
+pub const CONTENT_FIELD: &str = "$value";
+
+/// Deserializes a value from an XML node.
+pub fn from_node<'de, T>(node: &'de XmlNode) -> SCResult<T> where T: Deserialize<'de> {
+    T::deserialize(NodeDeserializer { node })
+}
+
+/// Serializes a value to an XML node with the given tag
+/// name. Elements of top-level sequences are named `item`.
+pub fn to_node<T>(name: &str, value: &T) -> SCResult<XmlNode> where T: Serialize + ?Sized {
+    let node = match value.serialize(ValueSerializer)? {
+        Serialized::Node(node) => node,
+        Serialized::Scalar(value) => XmlNode::new(name).content(&value).build(),
+        Serialized::Seq(items) => {
+            let mut parts = Parts::default();
+            for item in items {
+                parts.place_child("item", item)?;
+            }
+            parts.build()
+        },
+        Serialized::Empty => XmlNode::new(name).build()
+    };
+    Ok(node.renamed(name))
+}
+
+// Serialization
+
+/// An intermediate representation of a serialized value,
+/// which is placed into its parent once its name is known.
+enum Serialized {
+    Empty,
+    Scalar(String),
+    Node(XmlNode),
+    Seq(Vec<Serialized>)
+}
+
+/// The parts of a node under construction.
+#[derive(Default)]
+struct Parts {
+    content: String,
+    attributes: Vec<(String, String)>,
+    childs: Vec<XmlNode>
+}
+
+impl Parts {
+    /// Places a field's value as an attribute if it is
+    /// a scalar and as child elements otherwise.
+    fn place(&mut self, name: &str, value: Serialized) -> SCResult<()> {
+        match value {
+            Serialized::Scalar(value) if name == CONTENT_FIELD => self.content = value,
+            Serialized::Scalar(value) => self.attributes.push((name.to_owned(), value)),
+            value => self.place_child(name, value)?
+        }
+        Ok(())
+    }
+
+    /// Places a value as child elements.
+    fn place_child(&mut self, name: &str, value: Serialized) -> SCResult<()> {
+        match value {
+            Serialized::Empty => (),
+            Serialized::Scalar(value) => self.childs.push(XmlNode::new(name).content(&value).build()),
+            Serialized::Node(node) => self.childs.push(node.renamed(name)),
+            Serialized::Seq(items) => for item in items {
+                if let Serialized::Seq(_) = item {
+                    return Err("Nested sequences cannot be represented as XML".into());
+                }
+                self.place_child(name, item)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds an unnamed node from the parts.
+    fn build(self) -> XmlNode {
+        XmlNode::new("")
+            .content(&self.content)
+            .attributes(self.attributes)
+            .childs(self.childs)
+            .build()
+    }
+}
+
+/// Wraps a variant's data in an element named after it.
+fn wrap_variant(variant: &str, value: Serialized) -> SCResult<Serialized> {
+    let mut parts = Parts::default();
+    parts.place_child(variant, value)?;
+    Ok(Serialized::Node(parts.build()))
+}
+
+struct ValueSerializer;
+
+struct SeqSerializer {
+    variant: Option<&'static str>,
+    items: Vec<Serialized>
+}
+
+struct StructSerializer {
+    variant: Option<&'static str>,
+    parts: Parts,
+    key: Option<String>
+}
+
+impl SeqSerializer {
+    fn new(variant: Option<&'static str>, len: Option<usize>) -> Self {
+        Self { variant, items: Vec::with_capacity(len.unwrap_or(0)) }
+    }
+
+    fn push<T>(&mut self, value: &T) -> SCResult<()> where T: Serialize + ?Sized {
+        self.items.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> SCResult<Serialized> {
+        match self.variant {
+            Some(variant) => wrap_variant(variant, Serialized::Seq(self.items)),
+            None => Ok(Serialized::Seq(self.items))
+        }
+    }
+}
+
+impl StructSerializer {
+    fn new(variant: Option<&'static str>) -> Self {
+        Self { variant, parts: Parts::default(), key: None }
+    }
+
+    fn field<T>(&mut self, name: &str, value: &T) -> SCResult<()> where T: Serialize + ?Sized {
+        self.parts.place(name, value.serialize(ValueSerializer)?)
+    }
+
+    fn finish(self) -> SCResult<Serialized> {
+        let node = Serialized::Node(self.parts.build());
+        match self.variant {
+            Some(variant) => wrap_variant(variant, node),
+            None => Ok(node)
+        }
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident: $type:ty),*) => {
+        $(fn $method(self, value: $type) -> SCResult<Serialized> { Ok(Serialized::Scalar(value.to_string())) })*
+    };
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Serialized;
+    type Error = SCError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = StructSerializer;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = StructSerializer;
+
+    serialize_display!(
+        serialize_bool: bool,
+        serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64,
+        serialize_f32: f32, serialize_f64: f64,
+        serialize_char: char, serialize_str: &str
+    );
+
+    fn serialize_bytes(self, _value: &[u8]) -> SCResult<Serialized> { Err("Byte arrays cannot be represented as XML".into()) }
+
+    fn serialize_none(self) -> SCResult<Serialized> { Ok(Serialized::Empty) }
+
+    fn serialize_some<T>(self, value: &T) -> SCResult<Serialized> where T: Serialize + ?Sized { value.serialize(self) }
+
+    fn serialize_unit(self) -> SCResult<Serialized> { Ok(Serialized::Empty) }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> SCResult<Serialized> { Ok(Serialized::Empty) }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> SCResult<Serialized> {
+        Ok(Serialized::Scalar(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> SCResult<Serialized> where T: Serialize + ?Sized {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(self, _name: &'static str, _index: u32, variant: &'static str, value: &T) -> SCResult<Serialized> where T: Serialize + ?Sized {
+        wrap_variant(variant, value.serialize(self)?)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> SCResult<SeqSerializer> { Ok(SeqSerializer::new(None, len)) }
+
+    fn serialize_tuple(self, len: usize) -> SCResult<SeqSerializer> { Ok(SeqSerializer::new(None, Some(len))) }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> SCResult<SeqSerializer> { Ok(SeqSerializer::new(None, Some(len))) }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize) -> SCResult<SeqSerializer> {
+        Ok(SeqSerializer::new(Some(variant), Some(len)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> SCResult<StructSerializer> { Ok(StructSerializer::new(None)) }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> SCResult<StructSerializer> { Ok(StructSerializer::new(None)) }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> SCResult<StructSerializer> {
+        Ok(StructSerializer::new(Some(variant)))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Serialized;
+    type Error = SCError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> SCResult<()> where T: Serialize + ?Sized { self.push(value) }
+
+    fn end(self) -> SCResult<Serialized> { self.finish() }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Serialized;
+    type Error = SCError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> SCResult<()> where T: Serialize + ?Sized { self.push(value) }
+
+    fn end(self) -> SCResult<Serialized> { self.finish() }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Serialized;
+    type Error = SCError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> SCResult<()> where T: Serialize + ?Sized { self.push(value) }
+
+    fn end(self) -> SCResult<Serialized> { self.finish() }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Serialized;
+    type Error = SCError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> SCResult<()> where T: Serialize + ?Sized { self.push(value) }
+
+    fn end(self) -> SCResult<Serialized> { self.finish() }
+}
+
+impl ser::SerializeMap for StructSerializer {
+    type Ok = Serialized;
+    type Error = SCError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> SCResult<()> where T: Serialize + ?Sized {
+        match key.serialize(ValueSerializer)? {
+            Serialized::Scalar(key) => self.key = Some(key),
+            _ => return Err("Map keys have to be scalars to be represented as XML".into())
+        }
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> SCResult<()> where T: Serialize + ?Sized {
+        let key = self.key.take().ok_or_else(|| SCError::from("Map value serialized before its key"))?;
+        self.field(&key, value)
+    }
+
+    fn end(self) -> SCResult<Serialized> { self.finish() }
+}
+
+impl ser::SerializeStruct for StructSerializer {
+    type Ok = Serialized;
+    type Error = SCError;
+
+    fn serialize_field<T>(&mut self, name: &'static str, value: &T) -> SCResult<()> where T: Serialize + ?Sized { self.field(name, value) }
+
+    fn end(self) -> SCResult<Serialized> { self.finish() }
+}
+
+impl ser::SerializeStructVariant for StructSerializer {
+    type Ok = Serialized;
+    type Error = SCError;
+
+    fn serialize_field<T>(&mut self, name: &'static str, value: &T) -> SCResult<()> where T: Serialize + ?Sized { self.field(name, value) }
+
+    fn end(self) -> SCResult<Serialized> { self.finish() }
+}
+
+// Deserialization
+
+/// Deserializes from a single element.
+struct NodeDeserializer<'de> {
+    node: &'de XmlNode
+}
+
+/// Deserializes from the child elements of a
+/// parent which share the same tag name.
+struct ChildsDeserializer<'de> {
+    parent: &'de XmlNode,
+    name: &'de str,
+    nodes: Vec<&'de XmlNode>
+}
+
+/// Deserializes from an attribute or an element's contents.
+struct ValueDeserializer<'de> {
+    node: &'de XmlNode,
+    value: &'de str
+}
+
+/// A field of a node, either stored in an
+/// attribute/the contents or in child elements.
+enum Entry<'de> {
+    Value(&'de str),
+    Childs(Vec<&'de XmlNode>)
+}
+
+struct EntriesAccess<'de> {
+    node: &'de XmlNode,
+    entries: vec::IntoIter<(&'de str, Entry<'de>)>,
+    value: Option<(&'de str, Entry<'de>)>
+}
+
+struct ChildsAccess<'de> {
+    nodes: vec::IntoIter<&'de XmlNode>
+}
+
+struct VariantAccess<'de> {
+    node: &'de XmlNode,
+    variant: &'de str
+}
+
+impl<'de> NodeDeserializer<'de> {
+    /// Collects the node's attributes, child elements
+    /// and contents, which are included if non-empty or
+    /// if explicitly requested as a field.
+    fn entries(&self, fields: &'static [&'static str]) -> EntriesAccess<'de> {
+        let node = self.node;
+        let mut entries: Vec<(&'de str, Entry<'de>)> = node.attributes().iter()
+            .map(|(key, value)| (key.as_str(), Entry::Value(value.as_str())))
+            .collect();
+
+        for child in node.childs() {
+            match entries.iter_mut().find(|(key, _)| *key == child.name()) {
+                Some((_, Entry::Childs(childs))) => childs.push(child),
+                Some(_) => (),
+                None => entries.push((child.name(), Entry::Childs(vec![child])))
+            }
+        }
+
+        if !node.content().is_empty() || fields.contains(&CONTENT_FIELD) {
+            entries.push((CONTENT_FIELD, Entry::Value(node.content())));
+        }
+
+        EntriesAccess { node, entries: entries.into_iter(), value: None }
+    }
+}
+
+impl<'de> ChildsDeserializer<'de> {
+    /// Fetches the only element.
+    fn single(self) -> SCResult<NodeDeserializer<'de>> {
+        match self.nodes.as_slice() {
+            [node] => Ok(NodeDeserializer { node }),
+            [] => Err(SCError::MissingChild { path: self.parent.path().to_owned(), name: self.name.to_owned() }),
+            nodes => Err(format!("Expected a single <{}> in {}, but found {}", self.name, self.parent.path(), nodes.len()).into())
+        }
+    }
+}
+
+impl<'de> ValueDeserializer<'de> {
+    fn parse<T>(&self) -> SCResult<T> where T: std::str::FromStr, T::Err: std::fmt::Display {
+        self.value.parse().map_err(|e: T::Err| SCError::InvalidValue { path: self.node.path().to_owned(), value: self.value.to_owned(), message: e.to_string() })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident),*) => {
+        $(fn $method<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { visitor.$visit(self.parse()?) })*
+    };
+}
+
+macro_rules! deserialize_single {
+    ($($method:ident),*) => {
+        $(fn $method<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.single()?.$method(visitor) })*
+    };
+}
+
+impl<'de> Deserializer<'de> for NodeDeserializer<'de> {
+    type Error = SCError;
+
+    fn deserialize_any<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        if self.node.attributes().is_empty() && self.node.childs().is_empty() {
+            visitor.visit_borrowed_str(self.node.content())
+        } else {
+            self.deserialize_map(visitor)
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { visitor.visit_some(self) }
+
+    fn deserialize_unit<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { visitor.visit_unit() }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { visitor.visit_unit() }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        visitor.visit_seq(ChildsAccess { nodes: self.node.childs().iter().collect::<Vec<_>>().into_iter() })
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.deserialize_seq(visitor) }
+
+    fn deserialize_tuple_struct<V>(self, _name: &'static str, _len: usize, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { visitor.visit_map(self.entries(&[])) }
+
+    fn deserialize_struct<V>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        visitor.visit_map(self.entries(fields))
+    }
+
+    fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        match self.node.childs().first() {
+            Some(child) => visitor.visit_enum(VariantAccess { node: self.node, variant: child.name() }),
+            None => visitor.visit_enum(BorrowedStrDeserializer::new(self.node.content()))
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { visitor.visit_unit() }
+
+    fn deserialize_bool<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_bool(visitor) }
+    fn deserialize_i8<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_i8(visitor) }
+    fn deserialize_i16<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_i16(visitor) }
+    fn deserialize_i32<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_i32(visitor) }
+    fn deserialize_i64<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_i64(visitor) }
+    fn deserialize_u8<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_u8(visitor) }
+    fn deserialize_u16<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_u16(visitor) }
+    fn deserialize_u32<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_u32(visitor) }
+    fn deserialize_u64<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_u64(visitor) }
+    fn deserialize_f32<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_f32(visitor) }
+    fn deserialize_f64<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_f64(visitor) }
+    fn deserialize_char<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_char(visitor) }
+    fn deserialize_str<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_str(visitor) }
+    fn deserialize_string<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_string(visitor) }
+    fn deserialize_bytes<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_bytes(visitor) }
+    fn deserialize_byte_buf<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_byte_buf(visitor) }
+    fn deserialize_identifier<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.content().deserialize_identifier(visitor) }
+}
+
+impl<'de> NodeDeserializer<'de> {
+    /// Deserializes from the node's contents.
+    fn content(self) -> ValueDeserializer<'de> {
+        ValueDeserializer { node: self.node, value: self.node.content() }
+    }
+}
+
+impl<'de> Deserializer<'de> for ChildsDeserializer<'de> {
+    type Error = SCError;
+
+    deserialize_single!(
+        deserialize_any, deserialize_unit, deserialize_map, deserialize_ignored_any,
+        deserialize_bool, deserialize_i8, deserialize_i16, deserialize_i32, deserialize_i64,
+        deserialize_u8, deserialize_u16, deserialize_u32, deserialize_u64, deserialize_f32, deserialize_f64,
+        deserialize_char, deserialize_str, deserialize_string, deserialize_bytes, deserialize_byte_buf, deserialize_identifier
+    );
+
+    fn deserialize_option<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        if self.nodes.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self.single()?)
+        }
+    }
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        self.single()?.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        visitor.visit_seq(ChildsAccess { nodes: self.nodes.into_iter() })
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { self.deserialize_seq(visitor) }
+
+    fn deserialize_tuple_struct<V>(self, _name: &'static str, _len: usize, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_struct<V>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        self.single()?.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = SCError;
+
+    deserialize_parsed!(
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8, deserialize_i16 => visit_i16, deserialize_i32 => visit_i32, deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8, deserialize_u16 => visit_u16, deserialize_u32 => visit_u32, deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32, deserialize_f64 => visit_f64,
+        deserialize_char => visit_char
+    );
+
+    fn deserialize_any<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { visitor.visit_borrowed_str(self.value) }
+
+    fn deserialize_option<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { visitor.visit_some(self) }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        visitor.visit_enum(BorrowedStrDeserializer::new(self.value))
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> { visitor.visit_unit() }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> de::MapAccess<'de> for EntriesAccess<'de> {
+    type Error = SCError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> SCResult<Option<K::Value>> where K: de::DeserializeSeed<'de> {
+        match self.entries.next() {
+            Some((key, entry)) => {
+                self.value = Some((key, entry));
+                seed.deserialize(BorrowedStrDeserializer::new(key)).map(Some)
+            },
+            None => Ok(None)
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> SCResult<V::Value> where V: de::DeserializeSeed<'de> {
+        match self.value.take() {
+            Some((_, Entry::Value(value))) => seed.deserialize(ValueDeserializer { node: self.node, value }),
+            Some((name, Entry::Childs(nodes))) => seed.deserialize(ChildsDeserializer { parent: self.node, name, nodes }),
+            None => Err("Map value deserialized before its key".into())
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for ChildsAccess<'de> {
+    type Error = SCError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> SCResult<Option<T::Value>> where T: de::DeserializeSeed<'de> {
+        match self.nodes.next() {
+            Some(node) => seed.deserialize(NodeDeserializer { node }).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> { Some(self.nodes.len()) }
+}
+
+impl<'de> de::EnumAccess<'de> for VariantAccess<'de> {
+    type Error = SCError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> SCResult<(V::Value, Self)> where V: de::DeserializeSeed<'de> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::<SCError>::new(self.variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> {
+    /// Deserializes from the elements wrapping the variant's data.
+    fn childs(self) -> ChildsDeserializer<'de> {
+        ChildsDeserializer { parent: self.node, name: self.variant, nodes: self.node.childs_by_name(self.variant).collect() }
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess<'de> {
+    type Error = SCError;
+
+    fn unit_variant(self) -> SCResult<()> { Ok(()) }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> SCResult<T::Value> where T: de::DeserializeSeed<'de> {
+        seed.deserialize(self.childs())
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        self.childs().deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> SCResult<V::Value> where V: Visitor<'de> {
+        self.childs().deserialize_struct("", fields, visitor)
+    }
+}
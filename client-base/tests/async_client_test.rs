@@ -0,0 +1,50 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use async_trait::async_trait;
+use tokio::runtime::Builder;
+use socha_client_base::async_client::{AsyncSCClient, AsyncSCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use common::{Add, CountingPlugin, CountingState, Script, Team};
+
+/// An asynchronous delegate that adds as much as possible.
+struct GreedyDelegate;
+
+#[async_trait]
+impl AsyncSCClientDelegate for GreedyDelegate {
+    type Plugin = CountingPlugin;
+
+    async fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        Add { amount: 3 }
+    }
+}
+
+#[test]
+fn test_async_game() {
+    let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+    let (port, server) = common::serve(vec![Script::new().game("abc", 3).close()]);
+    let summaries = runtime.block_on(AsyncSCClient::new(GreedyDelegate).run("127.0.0.1", port, None)).unwrap();
+
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].turns.len(), 2);
+    let messages = &server.join().unwrap()[0];
+    assert_eq!(messages[0].name(), "join");
+    assert_eq!(messages[0].attribute("gameType").unwrap(), "counting");
+    assert_eq!(common::moves(messages), vec![Add { amount: 3 }; 2]);
+}
+
+#[test]
+fn test_async_join_escaping() {
+    // Reservation codes are escaped, since they are not under the client's control
+    let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+    let reservations = ["a\"b", "<c&d>"];
+    let (port, server) = common::serve(vec![Script::new().close(); 2]);
+    runtime.block_on(AsyncSCClient::new(GreedyDelegate).run("127.0.0.1", port, Some(reservations[0]))).unwrap();
+    runtime.block_on(AsyncSCClient::new(GreedyDelegate).run_multiple("127.0.0.1", port, &reservations)).unwrap();
+
+    let joins: Vec<_> = server.join().unwrap().into_iter().flatten().collect();
+    let codes: Vec<_> = joins.iter().map(|join| join.attribute("reservationCode").unwrap()).collect();
+    assert_eq!(codes, vec![reservations[0], reservations[0], reservations[1]]);
+}
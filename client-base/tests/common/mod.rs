@@ -0,0 +1,317 @@
+//! A minimal game and a scripted server shared by the
+//! integration tests, which lets them exercise the clients
+//! without depending on one of the plugins.
+#![allow(dead_code)]
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Cursor, Read, Write};
+use std::net::TcpListener;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use xml::reader::{EventReader, XmlEvent as XmlReadEvent};
+use socha_client_base::error::SCError;
+use socha_client_base::plugin::{HasPlayerColor, HasRules, HasTurn, SCPlugin};
+use socha_client_base::protocol::{Data, GameResult, PlayerScore, Room, ScoreAggregation, ScoreCause, ScoreDefinition, ScoreFragment};
+use socha_client_base::util::{HasOpponent, SCResult};
+use socha_client_base::xml_node::{FromXmlNode, IntoXmlNode, XmlNode, XmlNodeBuilder};
+
+/// The total at which the counting game ends.
+pub const GOAL: u32 = 10;
+
+/// A counting game in which both teams alternately add
+/// 1 to 3 to a shared total. Whoever reaches the goal wins.
+#[derive(Debug)]
+pub struct CountingPlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Team {
+    One,
+    Two
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode)]
+pub struct Player {
+    pub team: Team,
+    pub display_name: String
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+#[xml(name = "state")]
+pub struct CountingState {
+    pub turn: u32,
+    pub total: u32
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+#[xml(name = "data", class = "add")]
+pub struct Add {
+    pub amount: u32
+}
+
+impl SCPlugin for CountingPlugin {
+    type PlayerColor = Team;
+    type Player = Player;
+    type GameState = CountingState;
+    type Move = Add;
+
+    fn protocol_game_type<'a>() -> &'a str { "counting" }
+
+    fn player_colors() -> Vec<Team> { vec![Team::One, Team::Two] }
+}
+
+impl fmt::Display for Team {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::One => write!(f, "ONE"),
+            Self::Two => write!(f, "TWO")
+        }
+    }
+}
+
+impl FromStr for Team {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        match raw {
+            "ONE" => Ok(Self::One),
+            "TWO" => Ok(Self::Two),
+            _ => Err(SCError::Custom(format!("Unknown team: {}", raw)))
+        }
+    }
+}
+
+impl HasOpponent for Team {
+    fn opponent(self) -> Self {
+        match self {
+            Self::One => Self::Two,
+            Self::Two => Self::One
+        }
+    }
+}
+
+impl<'a> From<Player> for XmlNodeBuilder<'a> {
+    fn from(player: Player) -> Self {
+        XmlNode::new("player")
+            .attribute("team", player.team.to_string())
+            .attribute("displayName", player.display_name)
+    }
+}
+
+impl HasPlayerColor for Player {
+    type PlayerColor = Team;
+
+    fn player_color(&self) -> Team { self.team }
+}
+
+impl CountingState {
+    pub fn new() -> Self {
+        Self { turn: 0, total: 0 }
+    }
+}
+
+impl HasPlayerColor for CountingState {
+    type PlayerColor = Team;
+
+    fn player_color(&self) -> Team {
+        if self.turn.is_multiple_of(2) { Team::One } else { Team::Two }
+    }
+}
+
+impl HasTurn for CountingState {
+    fn turn(&self) -> u32 { self.turn }
+}
+
+impl HasRules for CountingState {
+    type Move = Add;
+
+    fn possible_moves(&self) -> Vec<Add> {
+        if self.is_game_over() {
+            Vec::new()
+        } else {
+            (1..=3).map(|amount| Add { amount }).collect()
+        }
+    }
+
+    fn validate_move(&self, game_move: &Add) -> SCResult<()> {
+        if self.is_game_over() || !(1..=3).contains(&game_move.amount) {
+            return Err(SCError::Custom(format!("Cannot add {} to {}", game_move.amount, self.total)));
+        }
+        Ok(())
+    }
+
+    fn perform_move(&mut self, game_move: Add) -> SCResult<()> {
+        self.validate_move(&game_move)?;
+        self.total += game_move.amount;
+        self.turn += 1;
+        Ok(())
+    }
+
+    fn is_game_over(&self) -> bool { self.total >= GOAL }
+
+    fn points(&self, color: Team) -> i32 {
+        // The team that moved last has reached the goal
+        if self.is_game_over() && color != self.player_color() { 1 } else { 0 }
+    }
+}
+
+/// Builds the messages a server sends to a client.
+pub struct Script {
+    xml: String
+}
+
+impl Script {
+    /// Starts a script with the initial `<protocol>` element.
+    pub fn new() -> Self {
+        Self { xml: "<protocol>".to_owned() }
+    }
+
+    /// Adds a message.
+    pub fn message(mut self, node: XmlNode) -> Self {
+        self.xml += &node.to_string();
+        self
+    }
+
+    /// Adds a message to a room.
+    pub fn room(self, room_id: &str, data: Data<CountingPlugin>) -> Self {
+        let node = XmlNode::try_from(Room::<CountingPlugin> { room_id: room_id.to_owned(), data }).unwrap();
+        self.message(node)
+    }
+
+    /// Adds a message with a single attribute.
+    pub fn simple(self, name: &str, attribute: Option<(&str, &str)>) -> Self {
+        let node = attribute.into_iter().fold(XmlNode::new(name), |node, (key, value)| node.attribute(key, value));
+        self.message(node.build())
+    }
+
+    /// Adds the messages of a game in which the client plays
+    /// team one and requests a move on each of its turns.
+    /// The opponent always adds 2 and the client is assumed to
+    /// add the given amount, since the script is fixed upfront.
+    pub fn game(mut self, room_id: &str, amount: u32) -> Self {
+        let mut state = CountingState::new();
+        self = self.simple("joined", Some(("roomId", room_id)))
+            .room(room_id, Data::WelcomeMessage { color: Team::One });
+        while !state.is_game_over() {
+            self = self.room(room_id, Data::Memento { state: state.clone() });
+            let game_move = if state.player_color() == Team::One {
+                self = self.room(room_id, Data::MoveRequest);
+                Add { amount }
+            } else {
+                Add { amount: 2 }
+            };
+            state.perform_move(game_move).unwrap();
+        }
+        let winner = state.winner();
+        self.room(room_id, Data::Memento { state })
+            .room(room_id, Data::GameResult(result(winner)))
+            .simple("left", Some(("roomId", room_id)))
+    }
+
+    /// Closes the connection, returning the script.
+    pub fn close(self) -> String {
+        self.simple("close", None).into_xml()
+    }
+
+    /// Returns the script, keeping the connection open.
+    pub fn into_xml(self) -> String {
+        self.xml
+    }
+}
+
+/// Creates a result in which the given team has won.
+pub fn result(winner: Option<Team>) -> GameResult<CountingPlugin> {
+    let fragment = |name: &str, aggregation, relevant_for_ranking| ScoreFragment { name: name.to_owned(), aggregation, relevant_for_ranking };
+    let score = |team| PlayerScore {
+        cause: ScoreCause::Regular,
+        reason: String::new(),
+        parts: if winner == Some(team) { vec![2.0, 1.5] } else if winner.is_none() { vec![1.0, 0.5] } else { vec![0.0, 0.5] }
+    };
+    GameResult {
+        definition: ScoreDefinition { fragments: vec![fragment("Siegpunkte", ScoreAggregation::Sum, true), fragment("Punkte", ScoreAggregation::Average, true)] },
+        scores: vec![score(Team::One), score(Team::Two)],
+        winners: winner.into_iter().map(|team| Player { team, display_name: team.to_string() }).collect()
+    }
+}
+
+/// A writer whose output can be inspected
+/// while the client still owns it.
+#[derive(Clone, Default)]
+pub struct SharedBuffer {
+    bytes: Arc<Mutex<Vec<u8>>>
+}
+
+impl SharedBuffer {
+    pub fn new() -> Self { Self::default() }
+
+    /// Parses the messages written so far.
+    pub fn messages(&self) -> Vec<XmlNode> {
+        read_messages(&self.bytes.lock().unwrap())
+    }
+
+    /// Parses the moves written so far.
+    pub fn moves(&self) -> Vec<Add> {
+        moves(&self.messages())
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+/// Creates a transport feeding the script
+/// to a client, which writes to the buffer.
+pub fn transport(script: String, output: &SharedBuffer) -> (Cursor<Vec<u8>>, SharedBuffer) {
+    (Cursor::new(script.into_bytes()), output.clone())
+}
+
+/// Parses the messages a client has sent
+/// inside of its `<protocol>` element.
+pub fn read_messages(bytes: &[u8]) -> Vec<XmlNode> {
+    let mut xml_reader = EventReader::new(bytes);
+    loop {
+        match xml_reader.next().unwrap() {
+            XmlReadEvent::StartElement { name, .. } if name.local_name == "protocol" => break,
+            _ => ()
+        }
+    }
+    // The client never closes its <protocol> element
+    let mut messages = Vec::new();
+    while let Ok(node) = XmlNode::read_from(&mut xml_reader) {
+        messages.push(node);
+    }
+    messages
+}
+
+/// Extracts the moves from the sent messages.
+pub fn moves(messages: &[XmlNode]) -> Vec<Add> {
+    messages.iter()
+        .filter(|m| m.name() == "room")
+        .map(|m| match <Room<CountingPlugin>>::from_node(m).unwrap().data {
+            Data::Move(game_move) => game_move,
+            data => panic!("Expected a move, got {:?}", data)
+        })
+        .collect()
+}
+
+/// Serves the scripts to the clients connecting via TCP,
+/// one per connection. Returns the port and a handle
+/// yielding the messages sent on each connection.
+pub fn serve(scripts: Vec<String>) -> (u16, JoinHandle<Vec<Vec<XmlNode>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || scripts.into_iter().map(|script| {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.write_all(script.as_bytes()).unwrap();
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).unwrap();
+        read_messages(&received)
+    }).collect());
+    (port, handle)
+}
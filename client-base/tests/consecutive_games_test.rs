@@ -0,0 +1,47 @@
+mod common;
+
+use std::sync::mpsc::{channel, Sender};
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use common::{Add, CountingPlugin, CountingState, Script, Team};
+
+/// A delegate that adds 3 and reports new games.
+struct CountingDelegate {
+    games: Sender<()>
+}
+
+impl SCClientDelegate for CountingDelegate {
+    type Plugin = CountingPlugin;
+
+    fn on_new_game(&mut self) {
+        self.games.send(()).unwrap();
+    }
+
+    fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        Add { amount: 3 }
+    }
+}
+
+#[test]
+fn test_consecutive_games() {
+    let scripts = ["a", "b", "c"].iter().map(|room_id| Script::new().game(room_id, 3).close()).collect();
+    let (port, server) = common::serve(scripts);
+    let (games, receiver) = channel();
+    let summaries = SCClient::new(CountingDelegate { games }, DebugMode::default())
+        .with_game_count(3)
+        .run("127.0.0.1", port, Some("res"))
+        .unwrap();
+
+    assert_eq!(summaries.iter().map(|s| s.room_id.as_str()).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    assert_eq!(receiver.try_iter().count(), 3);
+
+    // The reservation is only used to join the first game
+    let joins: Vec<_> = server.join().unwrap().into_iter().map(|messages| messages.into_iter().next().unwrap()).collect();
+    assert_eq!(joins[0].name(), "joinPrepared");
+    assert_eq!(joins[0].attribute("reservationCode").unwrap(), "res");
+    for join in &joins[1..] {
+        assert_eq!(join.name(), "join");
+        assert_eq!(join.attribute("gameType").unwrap(), "counting");
+    }
+}
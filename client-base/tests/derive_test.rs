@@ -0,0 +1,78 @@
+use xml::reader::EventReader;
+use socha_client_base::error::SCError;
+use socha_client_base::xml_node::{FromXmlNode, IntoXmlNode, XmlNode};
+
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+#[xml(name = "piece")]
+struct Piece {
+    #[xml(rename = "type")]
+    piece_type: String,
+    #[xml(content)]
+    label: String
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+#[xml(name = "data", class = "memento")]
+struct Memento {
+    current_turn: u32,
+    #[xml(default)]
+    comment: String,
+    #[xml(child, rename = "last")]
+    last_piece: Piece,
+    #[xml(childs, rename = "piece")]
+    pieces: Vec<Piece>,
+    #[xml(child_content)]
+    is_final: bool
+}
+
+fn reparse(node: XmlNode) -> XmlNode {
+    let xml = node.to_string();
+    XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).expect("Could not reparse node")
+}
+
+#[test]
+fn test_derive_round_trip() {
+    let piece = |t: &str| Piece { piece_type: t.to_owned(), label: format!("A {}", t) };
+    let memento = Memento {
+        current_turn: 3,
+        comment: "Hello".to_owned(),
+        last_piece: piece("BEE"),
+        pieces: vec![piece("ANT"), piece("SPIDER")],
+        is_final: false
+    };
+
+    let node = reparse(memento.clone().into());
+    assert_eq!(node.name(), "data");
+    assert_eq!(node.attribute("class").unwrap(), "memento");
+    assert_eq!(node.attribute("currentTurn").unwrap(), "3");
+    assert_eq!(node.child_by_name("last").unwrap().attribute("type").unwrap(), "BEE");
+    assert_eq!(node.child_by_name("isFinal").unwrap().content(), "false");
+    assert_eq!(Memento::from_node(&node).unwrap(), memento);
+}
+
+#[test]
+fn test_derive_errors() {
+    let xml = r#"<data class="memento" currentTurn="1"><last type="BEE">Bee</last><isFinal>true</isFinal></data>"#;
+    let node = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+    let memento = Memento::from_node(&node).unwrap();
+    assert_eq!(memento.comment, "");
+    assert!(memento.pieces.is_empty());
+
+    let xml = r#"<data class="memento" currentTurn="x" />"#;
+    let node = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+    assert!(matches!(Memento::from_node(&node), Err(SCError::InvalidValue { .. })));
+
+    let xml = r#"<data class="memento" currentTurn="1"><isFinal>true</isFinal></data>"#;
+    let node = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+    match Memento::from_node(&node) {
+        Err(SCError::MissingChild { path, name }) => {
+            assert_eq!(path, "data");
+            assert_eq!(name, "last");
+        },
+        result => panic!("Expected a missing child, got {:?}", result)
+    }
+
+    let xml = r#"<data class="move" />"#;
+    let node = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+    assert!(matches!(Memento::from_node(&node), Err(SCError::UnknownDataClass { .. })));
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use std::sync::mpsc::{channel, Sender};
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use common::{Add, CountingPlugin, CountingState, Script, SharedBuffer, Team};
+
+/// A delegate that reports the lengths of the history
+/// and the opponent's moves and always adds 3.
+struct HistoryDelegate {
+    lengths: Sender<(usize, usize)>,
+    opponent_moves: Sender<Add>
+}
+
+impl SCClientDelegate for HistoryDelegate {
+    type Plugin = CountingPlugin;
+
+    fn on_opponent_move(&mut self, game_move: &Add) {
+        self.opponent_moves.send(game_move.clone()).unwrap();
+    }
+
+    fn request_move(&mut self, state: &CountingState, _my_team: Team, history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        assert_eq!(history.current_state(), Some(state));
+        assert_eq!(history.state_at_turn(state.turn), Some(state));
+        assert_eq!(history.occurrences(state), 1);
+        self.lengths.send((history.states().len(), history.moves().len())).unwrap();
+        Add { amount: 3 }
+    }
+}
+
+#[test]
+fn test_game_history() {
+    let (lengths, length_receiver) = channel();
+    let (opponent_moves, _opponent_move_receiver) = channel();
+    let summaries = SCClient::new(HistoryDelegate { lengths, opponent_moves }, DebugMode::default())
+        .run_with(common::transport(Script::new().game("abc", 3).close(), &SharedBuffer::new()), None)
+        .unwrap();
+
+    assert_eq!(length_receiver.try_iter().collect::<Vec<_>>(), vec![(1, 0), (3, 1)]);
+    let history = &summaries[0].history;
+    assert_eq!(history.states().iter().map(|s| s.total).collect::<Vec<_>>(), vec![0, 3, 5, 8, 10]);
+    assert_eq!(history.moves(), &[(0, Add { amount: 3 }), (2, Add { amount: 3 })]);
+}
+
+#[test]
+fn test_opponent_moves() {
+    let (lengths, _length_receiver) = channel();
+    let (opponent_moves, opponent_move_receiver) = channel();
+    SCClient::new(HistoryDelegate { lengths, opponent_moves }, DebugMode::default())
+        .run_with(common::transport(Script::new().game("abc", 3).close(), &SharedBuffer::new()), None)
+        .unwrap();
+
+    // The opponent's moves are inferred from the states before and after
+    assert_eq!(opponent_move_receiver.try_iter().collect::<Vec<_>>(), vec![Add { amount: 2 }; 2]);
+}
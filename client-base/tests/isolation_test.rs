@@ -0,0 +1,75 @@
+mod common;
+
+use std::thread;
+use std::time::Duration;
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::ponder::PonderJob;
+use socha_client_base::protocol::GameResult;
+use common::{Add, CountingPlugin, CountingState, Script, SharedBuffer, Team};
+
+/// A delegate that panics in its hooks and
+/// while computing its first move.
+struct PanickingDelegate;
+
+impl SCClientDelegate for PanickingDelegate {
+    type Plugin = CountingPlugin;
+
+    fn on_update_state(&mut self, _state: &CountingState) { panic!("Could not update state"); }
+
+    fn on_game_end(&mut self, _result: GameResult<CountingPlugin>) { panic!("Could not end game"); }
+
+    fn on_welcome_message(&mut self, _color: &Team) { panic!("Could not welcome"); }
+
+    fn ponder(&mut self, _state: &CountingState, _my_team: Team) -> Option<PonderJob> { panic!("Could not ponder"); }
+
+    fn request_move(&mut self, state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        if state.turn == 0 {
+            panic!("Could not compute move");
+        }
+        Add { amount: 3 }
+    }
+}
+
+/// A delegate that returns its first move long
+/// after the deadline without publishing a candidate.
+struct HangingDelegate;
+
+impl SCClientDelegate for HangingDelegate {
+    type Plugin = CountingPlugin;
+
+    fn request_move(&mut self, state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        if state.turn == 0 {
+            thread::sleep(Duration::from_millis(200));
+        }
+        Add { amount: 3 }
+    }
+}
+
+#[test]
+fn test_panic_isolation() {
+    let output = SharedBuffer::new();
+    let summaries = SCClient::new(PanickingDelegate, DebugMode::default())
+        .run_with(common::transport(Script::new().game("abc", 3).close(), &output), None)
+        .unwrap();
+
+    // The first legal move replaces the panicking computation
+    assert_eq!(output.moves(), vec![Add { amount: 1 }, Add { amount: 3 }]);
+    assert_eq!(summaries[0].turns.iter().map(|t| t.fallback).collect::<Vec<_>>(), vec![true, false]);
+}
+
+#[test]
+fn test_watchdog() {
+    let output = SharedBuffer::new();
+    let summaries = SCClient::new(HangingDelegate, DebugMode::default())
+        .with_move_budget(Duration::from_millis(20))
+        .with_watchdog(Duration::from_millis(100))
+        .run_with(common::transport(Script::new().game("abc", 3).close(), &output), None)
+        .unwrap();
+
+    assert_eq!(output.moves()[0], Add { amount: 1 });
+    let first = &summaries[0].turns[0];
+    assert!(first.fallback && first.timed_out);
+    assert!(first.duration >= Duration::from_millis(100));
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use std::sync::mpsc::{channel, Sender};
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::xml_node::XmlNode;
+use common::{Add, CountingPlugin, CountingState, Script, SharedBuffer, Team};
+
+/// A delegate that reports its lifecycle events.
+struct LifecycleDelegate {
+    events: Sender<String>
+}
+
+impl SCClientDelegate for LifecycleDelegate {
+    type Plugin = CountingPlugin;
+
+    fn on_new_game(&mut self) { self.events.send("new game".to_owned()).unwrap(); }
+
+    fn on_welcome_message(&mut self, color: &Team) { self.events.send(format!("welcome {}", color)).unwrap(); }
+
+    fn on_joined(&mut self, room_id: &str) { self.events.send(format!("joined {}", room_id)).unwrap(); }
+
+    fn on_left(&mut self, room_id: &str) { self.events.send(format!("left {}", room_id)).unwrap(); }
+
+    fn on_server_error(&mut self, message: &str) { self.events.send(format!("error: {}", message)).unwrap(); }
+
+    fn on_close(&mut self) { self.events.send("close".to_owned()).unwrap(); }
+
+    fn on_unrecognized_message(&mut self, node: &XmlNode) { self.events.send(format!("unrecognized: {}", node.name())).unwrap(); }
+
+    fn on_move_sent(&mut self, game_move: &Add) { self.events.send(format!("sent {}", game_move.amount)).unwrap(); }
+
+    fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        Add { amount: 3 }
+    }
+}
+
+fn run(script: String) -> Vec<String> {
+    let (events, receiver) = channel();
+    SCClient::new(LifecycleDelegate { events }, DebugMode::default())
+        .run_with(common::transport(script, &SharedBuffer::new()), None)
+        .unwrap();
+    receiver.try_iter().collect()
+}
+
+#[test]
+fn test_game_lifecycle() {
+    assert_eq!(run(Script::new().game("abc", 3).close()), vec![
+        "new game", "joined abc", "welcome ONE", "sent 3", "sent 3", "left abc", "close"
+    ]);
+}
+
+#[test]
+fn test_errors_and_unrecognized_messages() {
+    let xml = r#"<protocol><room roomId="abc"><data class="error" message="Invalid move" /></room><unknown /><close /></protocol>"#;
+    assert_eq!(run(xml.to_owned()), vec!["new game", "error: Invalid move", "unrecognized: unknown", "close"]);
+}
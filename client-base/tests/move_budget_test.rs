@@ -0,0 +1,72 @@
+mod common;
+
+use std::thread;
+use std::time::Duration;
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use common::{Add, CountingPlugin, CountingState, Script, SharedBuffer, Team};
+
+/// A delegate that publishes a candidate, but only
+/// returns a different move after the deadline.
+struct SlowDelegate;
+
+impl SCClientDelegate for SlowDelegate {
+    type Plugin = CountingPlugin;
+
+    fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, budget: &MoveBudget<Add>) -> Add {
+        budget.publish(Add { amount: 2 });
+        while !budget.is_expired() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        // Returns a little later, so the candidate is sent first
+        thread::sleep(Duration::from_millis(10));
+        Add { amount: 3 }
+    }
+}
+
+/// A delegate that publishes a candidate
+/// and never returns afterwards.
+struct StuckDelegate;
+
+impl SCClientDelegate for StuckDelegate {
+    type Plugin = CountingPlugin;
+
+    fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, budget: &MoveBudget<Add>) -> Add {
+        budget.publish(Add { amount: 3 });
+        loop {
+            thread::park();
+        }
+    }
+}
+
+#[test]
+fn test_candidate_at_deadline() {
+    let output = SharedBuffer::new();
+    let summaries = SCClient::new(SlowDelegate, DebugMode::default())
+        .with_move_budget(Duration::from_millis(20))
+        .run_with(common::transport(Script::new().game("abc", 2).close(), &output), None)
+        .unwrap();
+
+    assert_eq!(output.moves(), vec![Add { amount: 2 }; 3]);
+    for turn in &summaries[0].turns {
+        assert!(turn.timed_out && !turn.fallback);
+        assert!(turn.duration >= Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_delegate_never_returning() {
+    // The candidate is sent for the first request, while the
+    // following ones fall back since the delegate is still busy
+    let output = SharedBuffer::new();
+    let summaries = SCClient::new(StuckDelegate, DebugMode::default())
+        .with_move_budget(Duration::from_millis(20))
+        .run_with(common::transport(Script::new().game("abc", 3).close(), &output), None)
+        .unwrap();
+
+    assert_eq!(output.moves(), vec![Add { amount: 3 }, Add { amount: 1 }]);
+    let turns = &summaries[0].turns;
+    assert!(turns[0].timed_out && !turns[0].fallback);
+    assert!(turns[1].fallback);
+}
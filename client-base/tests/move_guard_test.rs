@@ -0,0 +1,32 @@
+mod common;
+
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use common::{Add, CountingPlugin, CountingState, Script, SharedBuffer, Team};
+
+/// A delegate that adds more than allowed on its first turn.
+struct IllegalDelegate;
+
+impl SCClientDelegate for IllegalDelegate {
+    type Plugin = CountingPlugin;
+
+    fn request_move(&mut self, state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        Add { amount: if state.turn == 0 { 5 } else { 3 } }
+    }
+}
+
+fn sent_moves(move_guard: bool) -> Vec<Add> {
+    let output = SharedBuffer::new();
+    SCClient::new(IllegalDelegate, DebugMode::default())
+        .with_move_guard(move_guard)
+        .run_with(common::transport(Script::new().game("abc", 3).close(), &output), None)
+        .unwrap();
+    output.moves()
+}
+
+#[test]
+fn test_move_guard() {
+    assert_eq!(sent_moves(false), vec![Add { amount: 5 }, Add { amount: 3 }]);
+    assert_eq!(sent_moves(true), vec![Add { amount: 1 }, Add { amount: 3 }]);
+}
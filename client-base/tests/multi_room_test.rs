@@ -0,0 +1,80 @@
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::plugin::HasRules;
+use socha_client_base::protocol::{Data, Room};
+use socha_client_base::xml_node::FromXmlNode;
+use common::{Add, CountingPlugin, CountingState, Script, SharedBuffer, Team};
+
+/// A delegate that adds 1 on even totals and 2 on odd
+/// ones, which reveals the state it has been passed.
+struct ParityDelegate;
+
+impl SCClientDelegate for ParityDelegate {
+    type Plugin = CountingPlugin;
+
+    fn request_move(&mut self, state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        Add { amount: 1 + state.total % 2 }
+    }
+}
+
+#[test]
+fn test_multiple_rooms() {
+    let created = Arc::new(AtomicUsize::new(0));
+    let factory_created = created.clone();
+    let state_a = CountingState::new();
+    let state_b = state_a.after_move(Add { amount: 3 }).unwrap();
+    let script = Script::new()
+        .room("a", Data::WelcomeMessage { color: Team::One })
+        .room("b", Data::WelcomeMessage { color: Team::Two })
+        .room("a", Data::Memento { state: state_a })
+        .room("b", Data::Memento { state: state_b })
+        .room("b", Data::MoveRequest)
+        .room("a", Data::MoveRequest)
+        .close();
+
+    let output = SharedBuffer::new();
+    SCClient::new(ParityDelegate, DebugMode::default())
+        .with_delegate_factory(move || {
+            factory_created.fetch_add(1, Ordering::SeqCst);
+            ParityDelegate
+        })
+        .run_multiple_with(common::transport(script, &output), &["res-a", "res-b"])
+        .unwrap();
+
+    let messages = output.messages();
+    for (join, reservation) in messages.iter().zip(&["res-a", "res-b"]) {
+        assert_eq!(join.name(), "joinPrepared");
+        assert_eq!(join.attribute("reservationCode").unwrap(), *reservation);
+    }
+
+    // Moves have to be computed from the requesting room's state
+    let moves: Vec<_> = messages[2..].iter()
+        .map(|m| <Room<CountingPlugin>>::from_node(m).unwrap())
+        .map(|room| (room.room_id, room.data))
+        .collect();
+    assert_eq!(moves, vec![("b".to_owned(), Data::Move(Add { amount: 2 })), ("a".to_owned(), Data::Move(Add { amount: 1 }))]);
+    assert_eq!(created.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_multiple_rooms_without_factory() {
+    // Messages for rooms without a delegate are skipped
+    let script = Script::new()
+        .simple("joined", Some(("roomId", "a")))
+        .simple("joined", Some(("roomId", "b")))
+        .room("b", Data::MoveRequest)
+        .simple("room", Some(("roomId", "b")))
+        .simple("left", Some(("roomId", "b")))
+        .close();
+    let output = SharedBuffer::new();
+    let summaries = SCClient::new(ParityDelegate, DebugMode::default())
+        .run_with(common::transport(script, &output), None)
+        .unwrap();
+    assert!(summaries.is_empty());
+    assert_eq!(output.messages().len(), 1);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use std::time::Duration;
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::plugin::HasTurn;
+use socha_client_base::ponder::PonderJob;
+use common::{Add, CountingPlugin, CountingState, Script, SharedBuffer, Team};
+
+/// A delegate that ponders until cancelled
+/// and reports when each job starts and ends.
+struct PonderingDelegate {
+    events: Sender<String>
+}
+
+impl SCClientDelegate for PonderingDelegate {
+    type Plugin = CountingPlugin;
+
+    fn ponder(&mut self, state: &CountingState, my_team: Team) -> Option<PonderJob> {
+        assert_eq!(my_team, Team::One);
+        self.events.send(format!("ponder {}", state.turn())).unwrap();
+        let events = self.events.clone();
+        let turn = state.turn();
+        Some(Box::new(move |token| {
+            while !token.is_cancelled() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            events.send(format!("cancelled {}", turn)).unwrap();
+        }))
+    }
+
+    fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        self.events.send("move".to_owned()).unwrap();
+        Add { amount: 3 }
+    }
+}
+
+#[test]
+fn test_pondering_on_opponent_turns() {
+    let (events, receiver) = channel();
+    SCClient::new(PonderingDelegate { events }, DebugMode::default())
+        .run_with(common::transport(Script::new().game("abc", 3).close(), &SharedBuffer::new()), None)
+        .unwrap();
+
+    // Each job is cancelled once the opponent has moved
+    let events: Vec<_> = receiver.try_iter().collect();
+    assert_eq!(events, vec!["move", "ponder 1", "cancelled 1", "move", "ponder 3", "cancelled 3"]);
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use std::sync::mpsc::{channel, Sender};
+use std::{env, fs, process};
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::protocol::GameResult;
+use socha_client_base::recording::{read_recording, Direction};
+use common::{Add, CountingPlugin, CountingState, Script, SharedBuffer, Team};
+
+/// A delegate that adds 1 and reports the result.
+struct ReportingDelegate {
+    results: Sender<GameResult<CountingPlugin>>
+}
+
+impl SCClientDelegate for ReportingDelegate {
+    type Plugin = CountingPlugin;
+
+    fn on_game_end(&mut self, result: GameResult<CountingPlugin>) {
+        self.results.send(result).unwrap();
+    }
+
+    fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        Add { amount: 1 }
+    }
+}
+
+#[test]
+fn test_recording_replay() {
+    let path = env::temp_dir().join(format!("socha-recording-{}.xml", process::id()));
+    let (results, receiver) = channel();
+    let debug_mode = DebugMode { recording: Some(path.clone()), ..DebugMode::default() };
+    let summaries = SCClient::new(ReportingDelegate { results: results.clone() }, debug_mode)
+        .run_with(common::transport(Script::new().game("abc", 1).close(), &SharedBuffer::new()), None)
+        .unwrap();
+
+    let recording = read_recording(&path).unwrap();
+    assert_eq!(recording[0].direction, Direction::Sent);
+    assert_eq!(recording[0].node.name(), "join");
+    assert_eq!(recording.last().unwrap().direction, Direction::Received);
+    assert_eq!(recording.iter().filter(|m| m.direction == Direction::Sent).count(), 5);
+
+    // The replayed session ends with the same result
+    let replayed = SCClient::new(ReportingDelegate { results }, DebugMode::default()).replay(&path).unwrap();
+    assert_eq!(replayed[0].result, summaries[0].result);
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![summaries[0].result.clone(); 2]);
+    fs::remove_file(&path).unwrap();
+}
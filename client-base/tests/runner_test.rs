@@ -0,0 +1,79 @@
+mod common;
+
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::thread;
+use log::LevelFilter;
+use socha_client_base::client::SCClientDelegate;
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::runner::{self, RunnerArgs};
+use common::{Add, CountingPlugin, CountingState, Script, Team};
+
+/// A delegate that adds as much as possible.
+struct GreedyDelegate;
+
+impl SCClientDelegate for GreedyDelegate {
+    type Plugin = CountingPlugin;
+
+    fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        Add { amount: 3 }
+    }
+}
+
+fn parse(args: &[&str]) -> Result<RunnerArgs, String> {
+    RunnerArgs::parse(args.iter().map(|&a| a.to_owned()))
+}
+
+#[test]
+fn test_default_args() {
+    assert_eq!(parse(&[]), Ok(RunnerArgs::default()));
+}
+
+#[test]
+fn test_standard_args() {
+    let args = parse(&["-h", "example.com", "--port", "1234", "-r", "abc", "-v", "--log-file", "client.log", "--debug-writer", "--record", "game.xml", "--telemetry", "turns.jsonl"]).unwrap();
+    assert_eq!(args.host, "example.com");
+    assert_eq!(args.port, 1234);
+    assert_eq!(args.reservation.as_deref(), Some("abc"));
+    assert_eq!(args.log_level, LevelFilter::Debug);
+    assert_eq!(args.log_file, Some(PathBuf::from("client.log")));
+    assert_eq!(args.games, 1);
+    assert_eq!(args.telemetry, Some(PathBuf::from("turns.jsonl")));
+
+    let debug_mode = args.debug_mode();
+    assert!(!debug_mode.debug_reader);
+    assert!(debug_mode.debug_writer);
+    assert_eq!(debug_mode.recording, Some(PathBuf::from("game.xml")));
+}
+
+#[test]
+fn test_invalid_args() {
+    assert!(parse(&["--port", "abc"]).is_err());
+    assert!(parse(&["--host"]).is_err());
+    assert!(parse(&["--games", "-1"]).is_err());
+    assert!(parse(&["--unknown"]).is_err());
+}
+
+#[test]
+fn test_game_count() {
+    assert_eq!(parse(&["--games", "5"]).unwrap().games, 5);
+}
+
+#[test]
+fn test_exit_codes() {
+    let args = |port: u16| vec!["--host".to_owned(), "127.0.0.1".to_owned(), "--port".to_owned(), port.to_string(), "--quiet".to_owned()];
+    let (port, server) = common::serve(vec![Script::new().game("abc", 3).close()]);
+    assert_eq!(runner::run(args(port), || GreedyDelegate), runner::EXIT_SUCCESS);
+    assert_eq!(common::moves(&server.join().unwrap()[0]), vec![Add { amount: 3 }; 2]);
+
+    // The listener stays bound while closing the connection
+    // immediately, so no other process can take over the port
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let closing = thread::spawn(move || drop(listener.accept().unwrap()));
+    assert_eq!(runner::run(args(port), || GreedyDelegate), runner::EXIT_FAILURE);
+    closing.join().unwrap();
+
+    assert_eq!(runner::run(vec!["--unknown".to_owned()], || GreedyDelegate), runner::EXIT_USAGE);
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "serde")]
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use xml::reader::EventReader;
+use socha_client_base::error::SCError;
+use socha_client_base::xml_node::XmlNode;
+use socha_client_base::xml_serde::{from_node, to_node};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Rect { width: u32, height: u32 }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    item_id: u32,
+    #[serde(rename = "$value")]
+    label: String
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Scene {
+    name: String,
+    comment: Option<String>,
+    shape: Shape,
+    outline: Shape,
+    #[serde(default)]
+    items: Vec<Item>,
+    #[serde(default)]
+    tags: Vec<String>,
+    scores: HashMap<String, i32>
+}
+
+fn reparse(node: XmlNode) -> XmlNode {
+    let xml = node.to_string();
+    XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).expect("Could not reparse node")
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let scene = Scene {
+        name: "Test".to_owned(),
+        comment: None,
+        shape: Shape::Empty,
+        outline: Shape::Rect { width: 3, height: 4 },
+        items: vec![Item { item_id: 1, label: "One".to_owned() }, Item { item_id: 2, label: "Two".to_owned() }],
+        tags: vec!["a".to_owned()],
+        scores: vec![("red".to_owned(), 2)].into_iter().collect()
+    };
+
+    let node = reparse(to_node("scene", &scene).unwrap());
+    assert_eq!(node.name(), "scene");
+    assert_eq!(node.attribute("name").unwrap(), "Test");
+    assert_eq!(node.attribute("shape").unwrap(), "Empty");
+    assert_eq!(node.childs_by_name("items").count(), 2);
+    assert_eq!(node.child_by_name("items").unwrap().content(), "One");
+    assert_eq!(from_node::<Scene>(&node).unwrap(), scene);
+
+    let empty = Scene { outline: Shape::Circle(1.5), items: Vec::new(), tags: Vec::new(), comment: Some("Hi".to_owned()), ..scene };
+    let node = reparse(to_node("scene", &empty).unwrap());
+    assert_eq!(from_node::<Scene>(&node).unwrap(), empty);
+}
+
+#[test]
+fn test_serde_errors() {
+    let xml = r#"<scene name="Test" shape="Empty"><outline><Circle>abc</Circle></outline><scores /></scene>"#;
+    let node = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+    match from_node::<Scene>(&node) {
+        Err(SCError::InvalidValue { path, value, .. }) => {
+            assert_eq!(path, "scene/outline[0]/Circle[0]");
+            assert_eq!(value, "abc");
+        },
+        result => panic!("Expected an invalid value, got {:?}", result)
+    }
+
+    let xml = r#"<scene name="Test" shape="Empty"><scores /></scene>"#;
+    let node = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+    assert!(from_node::<Scene>(&node).is_err());
+}
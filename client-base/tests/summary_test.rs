@@ -0,0 +1,58 @@
+mod common;
+
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::plugin::HasRules;
+use socha_client_base::summary::{self, GameSummary};
+use common::{Add, CountingPlugin, CountingState, Script, SharedBuffer, Team};
+
+/// A delegate that always adds the same amount.
+struct ConstantDelegate {
+    amount: u32
+}
+
+impl SCClientDelegate for ConstantDelegate {
+    type Plugin = CountingPlugin;
+
+    fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        Add { amount: self.amount }
+    }
+}
+
+fn play(amount: u32) -> GameSummary<CountingPlugin> {
+    let mut summaries = SCClient::new(ConstantDelegate { amount }, DebugMode::default())
+        .run_with(common::transport(Script::new().game("abc", amount).close(), &SharedBuffer::new()), None)
+        .unwrap();
+    assert_eq!(summaries.len(), 1);
+    summaries.remove(0)
+}
+
+#[test]
+fn test_game_summary() {
+    let summary = play(1);
+    assert_eq!(summary.room_id, "abc");
+    assert_eq!(summary.my_color, Some(Team::One));
+    assert_eq!(summary.result, common::result(Some(Team::One)));
+    assert!(summary.final_state.as_ref().unwrap().is_game_over());
+    assert_eq!(summary.history.current_state(), summary.final_state.as_ref());
+
+    assert_eq!(summary.turns.iter().map(|t| t.turn).collect::<Vec<_>>(), vec![0, 2, 4, 6]);
+    assert_eq!(summary.history.moves().len(), summary.turns.len());
+    assert_eq!(summary.timeouts(), 0);
+    assert!(summary.turns.iter().all(|t| !t.fallback));
+    assert_eq!(summary.total_move_time(), summary.turns.iter().map(|t| t.duration).sum());
+}
+
+#[test]
+fn test_scores_and_ranks() {
+    let won = play(1);
+    let lost = play(3);
+    assert!(won.did_win() && !lost.did_win());
+    assert_eq!((won.rank(), lost.rank()), (Some(1), Some(2)));
+    assert_eq!(won.my_score().unwrap().parts, vec![2.0, 1.5]);
+
+    // Win points are summed up, while points are averaged
+    assert_eq!(summary::aggregate_scores(&[won, lost]), vec![2.0, 1.0]);
+    assert!(summary::aggregate_scores::<CountingPlugin>(&[]).is_empty());
+}
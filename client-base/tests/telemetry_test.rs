@@ -0,0 +1,105 @@
+mod common;
+
+use std::sync::mpsc::{channel, Sender};
+use std::time::Duration;
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::summary::TurnStats;
+use socha_client_base::telemetry::{JsonLinesSink, TelemetrySink, TurnRecord};
+use socha_client_base::util::SCResult;
+use common::{Add, CountingPlugin, CountingState, Script, SharedBuffer, Team};
+
+/// A delegate that adds 3 and reports a metric.
+struct MeasuringDelegate;
+
+impl SCClientDelegate for MeasuringDelegate {
+    type Plugin = CountingPlugin;
+
+    fn move_metrics(&mut self) -> Vec<(String, f64)> {
+        vec![("nodes".to_owned(), 1.0)]
+    }
+
+    fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        Add { amount: 3 }
+    }
+}
+
+/// A telemetry sink forwarding the records.
+struct ForwardingSink {
+    records: Sender<TurnRecord<CountingPlugin>>
+}
+
+impl TelemetrySink<CountingPlugin> for ForwardingSink {
+    fn record(&mut self, record: &TurnRecord<CountingPlugin>) -> SCResult<()> {
+        self.records.send(record.clone()).unwrap();
+        Ok(())
+    }
+}
+
+fn record(room_id: &str, metrics: Vec<(String, f64)>) -> TurnRecord<CountingPlugin> {
+    TurnRecord {
+        room_id: room_id.to_owned(),
+        color: Team::Two,
+        stats: TurnStats { turn: 3, duration: Duration::from_millis(12), timed_out: false, fallback: true },
+        state: CountingState { turn: 3, total: 5 },
+        sent_move: Add { amount: 2 },
+        metrics
+    }
+}
+
+/// Lists the move's attributes in a fixed order,
+/// since the order of XML attributes is unspecified.
+fn normalize(json: &str) -> String {
+    json.replace(r#"<data amount=\"2\" class=\"add\" />"#, r#"<data class=\"add\" amount=\"2\" />"#)
+}
+
+#[test]
+fn test_turn_record_json() {
+    let record = record("abc", vec![("nodes".to_owned(), 1000.0), ("eval".to_owned(), f64::NAN)]);
+    assert_eq!(record.legal_moves(), 3);
+    assert_eq!(normalize(&record.to_json()), concat!(
+        r#"{"room_id":"abc","turn":3,"color":"TWO","elapsed_ms":12,"timed_out":false,"fallback":true,"legal_moves":3,"#,
+        r#""move":"<data class=\"add\" amount=\"2\" />","metrics":{"nodes":1000,"eval":null}}"#
+    ));
+}
+
+#[test]
+fn test_json_escaping() {
+    let record = record("\"a\\b\"\n\u{1}", vec![("tab\t\u{1f}".to_owned(), -0.5)]);
+    assert_eq!(normalize(&record.to_json()), concat!(
+        r#"{"room_id":"\"a\\b\"\n\u0001","turn":3,"color":"TWO","elapsed_ms":12,"timed_out":false,"fallback":true,"legal_moves":3,"#,
+        r#""move":"<data class=\"add\" amount=\"2\" />","metrics":{"tab\t\u001f":-0.5}}"#
+    ));
+}
+
+#[test]
+fn test_json_lines_sink() {
+    let record = record("abc", Vec::new());
+    let mut sink = JsonLinesSink::new(Vec::new());
+    sink.record(&record).unwrap();
+    sink.record(&record).unwrap();
+
+    let output = String::from_utf8(sink.into_inner().unwrap()).unwrap();
+    let lines: Vec<_> = output.lines().map(normalize).collect();
+    assert_eq!(lines, vec![normalize(&record.to_json()); 2]);
+}
+
+#[test]
+fn test_client_telemetry() {
+    let (records, receiver) = channel();
+    let summaries = SCClient::new(MeasuringDelegate, DebugMode::default())
+        .with_telemetry(ForwardingSink { records })
+        .run_with(common::transport(Script::new().game("abc", 3).close(), &SharedBuffer::new()), None)
+        .unwrap();
+
+    let records: Vec<_> = receiver.try_iter().collect();
+    assert_eq!(records.iter().map(|r| r.stats.clone()).collect::<Vec<_>>(), summaries[0].turns);
+    for record in &records {
+        assert_eq!(record.room_id, "abc");
+        assert_eq!(record.color, Team::One);
+        assert_eq!(record.state.turn, record.stats.turn);
+        assert_eq!(record.sent_move, Add { amount: 3 });
+        assert_eq!(record.metrics, vec![("nodes".to_owned(), 1.0)]);
+    }
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use std::io::Write;
+use std::thread;
+use xml::reader::{EventReader, XmlEvent as XmlReadEvent};
+use xml::writer::EmitterConfig;
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::plugin::HasRules;
+use socha_client_base::protocol::{Data, Room};
+use socha_client_base::transport::{MemoryTransport, Transport};
+use socha_client_base::xml_node::{FromXmlNode, XmlNode};
+use common::{Add, CountingPlugin, CountingState, Script, Team};
+
+/// A delegate that adds as much as possible.
+struct GreedyDelegate;
+
+impl SCClientDelegate for GreedyDelegate {
+    type Plugin = CountingPlugin;
+
+    fn request_move(&mut self, _state: &CountingState, _my_team: Team, _history: &GameHistory<CountingPlugin>, _budget: &MoveBudget<Add>) -> Add {
+        Add { amount: 3 }
+    }
+}
+
+#[test]
+fn test_memory_transport_exchange() {
+    let (client_end, server_end) = MemoryTransport::pair();
+    let client = thread::spawn(move || {
+        SCClient::new(GreedyDelegate, DebugMode::default()).run_with(client_end, None)
+    });
+
+    let (reader, writer) = server_end.split().unwrap();
+    let mut xml_reader = EventReader::new(reader);
+    let mut emitter_config = EmitterConfig::new();
+    emitter_config.write_document_declaration = false;
+    let mut xml_writer = emitter_config.create_writer(writer);
+
+    // Skips the client's initial <protocol> element
+    loop {
+        match xml_reader.next().unwrap() {
+            XmlReadEvent::StartElement { name, .. } if name.local_name == "protocol" => break,
+            _ => ()
+        }
+    }
+    let join = XmlNode::read_from(&mut xml_reader).unwrap();
+    assert_eq!(join.name(), "join");
+    assert_eq!(join.attribute("gameType").unwrap(), "counting");
+
+    // The move is only requested after the join message has arrived
+    let state = CountingState::new();
+    let script = Script::new()
+        .room("test", Data::WelcomeMessage { color: Team::One })
+        .room("test", Data::Memento { state: state.clone() })
+        .room("test", Data::MoveRequest);
+    xml_writer.inner_mut().write_all(script.into_xml().as_bytes()).unwrap();
+    xml_writer.inner_mut().flush().unwrap();
+
+    let room = <Room<CountingPlugin>>::from_node(&XmlNode::read_from(&mut xml_reader).unwrap()).unwrap();
+    assert_eq!(room.room_id, "test");
+    match room.data {
+        Data::Move(game_move) => assert!(state.validate_move(&game_move).is_ok()),
+        data => panic!("Expected a move, got {:?}", data)
+    }
+
+    XmlNode::new("sc.protocol.responses.CloseConnection").build().write_to(&mut xml_writer).unwrap();
+    client.join().unwrap().unwrap();
+}
@@ -1,3 +1,5 @@
+use xml::reader::EventReader;
+use socha_client_base::error::SCError;
 use socha_client_base::xml_node::XmlNode;
 
 #[test]
@@ -8,3 +10,36 @@ fn test_xml_display() {
         .build();
     assert_eq!(format!("{}", node), "<a b=\"c\">\n  <d />\n</a>".to_owned());
 }
+
+#[test]
+fn test_xml_paths() {
+    let xml = r#"<a><b /><c><b /></c><b /></a>"#;
+    let node = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+    let paths: Vec<_> = node.childs().iter().map(XmlNode::path).collect();
+    assert_eq!(node.path(), "a");
+    assert_eq!(paths, vec!["a/b[0]", "a/c[0]", "a/b[1]"]);
+    assert_eq!(node.child_by_name("c").unwrap().child_by_name("b").unwrap().path(), "a/c[0]/b[0]");
+}
+
+#[test]
+fn test_xml_errors() {
+    let xml = r#"<room roomId="abc"><data class="memento"><state><board><field x="1" /><field x="a" /></board></state></data></room>"#;
+    let node = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+    let board = node.child_by_name("data").unwrap().child_by_name("state").unwrap().child_by_name("board").unwrap();
+    let fields: Vec<_> = board.childs_by_name("field").collect();
+    assert_eq!(fields[0].path(), "room/data[0]/state[0]/board[0]/field[0]");
+    assert_eq!(fields[0].parse_attribute::<i32>("x").unwrap(), 1);
+
+    match fields[1].parse_attribute::<i32>("x") {
+        Err(SCError::InvalidValue { path, value, .. }) => {
+            assert_eq!(path, "room/data[0]/state[0]/board[0]/field[1]");
+            assert_eq!(value, "a");
+        },
+        result => panic!("Expected an invalid value, got {:?}", result)
+    }
+    match fields[1].attribute("y") {
+        Err(e @ SCError::MissingAttribute { .. }) => assert_eq!(e.to_string(), "No attribute with key 'y' found in room/data[0]/state[0]/board[0]/field[1]"),
+        result => panic!("Expected a missing attribute, got {:?}", result)
+    }
+    assert!(matches!(board.child_by_name("piece"), Err(SCError::MissingChild { .. })));
+}
@@ -3,20 +3,22 @@ use std::{cmp::max, cmp::min, collections::HashMap, collections::HashSet, collec
 use arrayvec::ArrayVec;
 use itertools::Itertools;
 use log::{debug, trace};
-use socha_client_base::{util::HasOpponent, util::SCResult, xml_node::{FromXmlNode, XmlNode}};
+use socha_client_base::{util::HasOpponent, util::SCResult, xml_node::{FromXmlNode, XmlNode, XmlNodeBuilder}};
 
 use crate::util::{AxialCoords, CubeCoords, DoubledCoords};
 
-use super::{Field, Piece, PieceType, PlayerColor};
+use super::{Field, Piece, PieceType, PlayerColor, PositionedField};
 
 /// The game board which is a symmetric hex grid with
 /// a side length of 6 fields.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Board {
     // TODO: Store fields contiguously in a Vec
     // or ideally a fixed-size container such
     // as an array or an ArrayVec.
     // and convert between coords and indices
+    #[cfg_attr(feature = "serde", serde(with = "serde_fields"))]
     fields: HashMap<AxialCoords, Field>
 }
 
@@ -40,8 +42,8 @@ impl Board {
                 .map(move |x| AxialCoords::new(x, y)));
         
         for coords in all_coords {
-            if !fields_mut.contains_key(&coords) {
-                fields_mut.insert(coords, Field::default());
+            if let std::collections::hash_map::Entry::Vacant(e) = fields_mut.entry(coords) {
+                e.insert(Field::default());
                 trace!("Filling up field at {}", coords);
             }
         }
@@ -122,7 +124,7 @@ impl Board {
             .map(|(c, f)| (AxialCoords::from(c - center), f))
             .collect();
         debug!("Fields: {:?}", fields);
-        Ok(Board { fields: fields })
+        Ok(Board { fields })
     }
 
     /// Fetches a reference to the field at the given
@@ -184,7 +186,7 @@ impl Board {
     
     /// Fetches the (existing) neighbor fields on the board.
     #[inline]
-    pub fn neighbors<'a>(&'a self, coords: impl Into<AxialCoords>) -> impl Iterator<Item=(AxialCoords, &Field)> + 'a {
+    pub fn neighbors<'a>(&'a self, coords: impl Into<AxialCoords>) -> impl Iterator<Item=(AxialCoords, &'a Field)> + 'a {
         coords.into().coord_neighbors().into_iter().filter_map(move |c| self.field(c).map(|f| (c, f)))
     }
     
@@ -199,6 +201,24 @@ impl Board {
         self.fields().flat_map(|(_, f)| f.piece_stack()).any(|&p| p == bee)
     }
     
+    /// Finds the coordinates of the given color's bee, if placed.
+    pub fn bee_coords(&self, color: PlayerColor) -> Option<AxialCoords> {
+        let bee = Piece { piece_type: PieceType::Bee, owner: color };
+        self.fields().find(|(_, f)| f.piece_stack().contains(&bee)).map(|(c, _)| c)
+    }
+    
+    /// Counts the neighbors of the given coordinates that are
+    /// either occupied or outside of the board.
+    pub fn blocked_neighbor_count(&self, coords: impl Into<AxialCoords>) -> usize {
+        coords.into().coord_neighbors().iter().filter(|&&c| self.is_occupied(c)).count()
+    }
+    
+    /// Tests whether the bee of the given color is surrounded
+    /// on all sides (which ends the game).
+    pub fn is_bee_surrounded(&self, color: PlayerColor) -> bool {
+        self.bee_coords(color).map(|c| self.blocked_neighbor_count(c) == 6).unwrap_or(false)
+    }
+    
     /// Tests whether the field at the given coordinates is next to
     /// a given color.
     pub fn is_next_to(&self, color: PlayerColor, coords: impl Into<AxialCoords>) -> bool {
@@ -297,7 +317,7 @@ impl Board {
         let a_neighbors: HashSet<_> = self.neighbors(a).collect();
         let b_neighbors: HashSet<_> = self.neighbors(b).collect();
         a_neighbors.intersection(&b_neighbors)
-            .filter(|(c, f)| f.piece_stack().len() != 1 || exception == Some(*c))
+            .filter(|(c, f)| f.piece_stack().len() != 1 || exception != Some(*c))
             .cloned().collect()
     }
     
@@ -316,12 +336,12 @@ impl Board {
     }
     
     /// Finds the accessible neighbors, optionally except an ignored field.
-    pub fn accessible_neighbors_except<'a>(&'a self, exception: Option<AxialCoords>, coords: impl Into<AxialCoords> + Copy + 'a) -> impl Iterator<Item=(AxialCoords, &Field)> + 'a {
+    pub fn accessible_neighbors_except<'a>(&'a self, exception: Option<AxialCoords>, coords: impl Into<AxialCoords> + Copy + 'a) -> impl Iterator<Item=(AxialCoords, &'a Field)> + 'a {
         self.neighbors(coords).filter(move |(c, f)| f.is_empty() && self.can_move_between_except(exception, coords, *c))
     }
     
     /// Finds the accessible neighbors.
-    pub fn accessible_neighbors<'a>(&'a self, coords: impl Into<AxialCoords> + Copy + 'a) -> impl Iterator<Item=(AxialCoords, &Field)> + 'a {
+    pub fn accessible_neighbors<'a>(&'a self, coords: impl Into<AxialCoords> + Copy + 'a) -> impl Iterator<Item=(AxialCoords, &'a Field)> + 'a {
         self.neighbors(coords).filter(move |(c, f)| f.is_empty() && self.can_move_between(coords, *c))
     }
     
@@ -377,9 +397,9 @@ impl FromXmlNode for Board {
             .flat_map(|child| child.childs_by_name("field")
                 .map(|f| Ok((
                     CubeCoords::new(
-                        f.attribute("x")?.parse()?,
-                        f.attribute("y")?.parse()?,
-                        f.attribute("z")?.parse()?
+                        f.parse_attribute("x")?,
+                        f.parse_attribute("y")?,
+                        f.parse_attribute("z")?
                     ).into(),
                     Field::from_node(f)?
                 ))))
@@ -387,3 +407,37 @@ impl FromXmlNode for Board {
         ))
     }
 }
+
+impl From<Board> for XmlNode {
+    fn from(board: Board) -> Self {
+        // Like the server, group the fields into columns
+        let mut fields: Vec<_> = board.fields.into_iter().collect();
+        fields.sort_by_key(|(c, _)| (c.x(), c.y()));
+        let columns: Vec<_> = fields.into_iter()
+            .group_by(|(c, _)| c.x())
+            .into_iter()
+            .map(|(_, column)| XmlNode::new("fields")
+                .childs(column.map(|(coords, field)| XmlNodeBuilder::from(PositionedField { coords, field }).name("field").build()))
+                .build())
+            .collect();
+        XmlNode::new("board").childs(columns).build()
+    }
+}
+
+/// Stores the fields as a list of positioned fields,
+/// since most formats only support strings as map keys.
+#[cfg(feature = "serde")]
+mod serde_fields {
+    use std::collections::HashMap;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use crate::util::AxialCoords;
+    use super::{Field, PositionedField};
+
+    pub fn serialize<S>(fields: &HashMap<AxialCoords, Field>, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.collect_seq(fields.iter().map(|(&coords, field)| PositionedField { field: field.clone(), coords }))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<AxialCoords, Field>, D::Error> where D: Deserializer<'de> {
+        Ok(Vec::<PositionedField>::deserialize(deserializer)?.into_iter().map(|f| (f.coords, f.field)).collect())
+    }
+}
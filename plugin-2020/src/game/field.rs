@@ -13,7 +13,9 @@ use super::{Piece, PieceType, PlayerColor};
 /// `PositionedField` or a tuple, depending on whether you
 /// want to express ownership over the field.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Field {
+    #[cfg_attr(feature = "serde", serde(default))]
     piece_stack: Vec<Piece>,
     is_obstructed: bool
 }
@@ -21,7 +23,7 @@ pub struct Field {
 impl Field {
     /// Creates a new field.
     pub fn new(piece_stack: impl IntoIterator<Item=Piece>, is_obstructed: bool) -> Self {
-        Self { piece_stack: piece_stack.into_iter().collect(), is_obstructed: is_obstructed }
+        Self { piece_stack: piece_stack.into_iter().collect(), is_obstructed }
     }
 
     /// Fetches the player color "owning" the field.
@@ -88,7 +90,7 @@ impl FromStr for Field {
             let groups = FIELD_SYNTAX.captures(raw).ok_or_else(|| SCError::from(format!("{} does not match field syntax {}", raw, FIELD_SYNTAX.as_str())))?;
             let owner = PlayerColor::try_from(groups[1].chars().next().unwrap())?;
             let piece_type = PieceType::try_from(groups[2].chars().next().unwrap())?;
-            let piece = Piece { piece_type: piece_type, owner: owner };
+            let piece = Piece { piece_type, owner };
             Ok(Self { piece_stack: vec![piece], is_obstructed: false })
         }
     }
@@ -108,7 +110,7 @@ impl FromXmlNode for Field {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
         Ok(Self {
             piece_stack: node.childs_by_name("piece").map(Piece::from_node).collect::<Result<_, _>>()?,
-            is_obstructed: node.attribute("isObstructed")?.parse()?
+            is_obstructed: node.parse_attribute("isObstructed")?
         })
     }
 }
@@ -1,14 +1,15 @@
 use log::trace;
-use socha_client_base::{util::HasOpponent, util::SCResult, xml_node::FromXmlNode, xml_node::XmlNode};
+use socha_client_base::{util::HasOpponent, util::SCResult, xml_node::{FromXmlNode, XmlNode, XmlNodeBuilder}};
 
 use crate::util::{Adjacentable, AxialCoords, LineFormable};
 
-use super::{Board, INITIAL_PIECE_TYPES, Move, Piece, PieceType, Player, PlayerColor, PositionedField};
+use super::{Board, Field, INITIAL_PIECE_TYPES, ROUND_LIMIT, Move, Piece, PieceType, Player, PlayerColor, PositionedField};
 
 /// A snapshot of the game's state at
 /// a specific turn. Consists of the
 /// board and information about both players.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameState {
     pub turn: u32,
     pub start_player_color: PlayerColor,
@@ -16,11 +17,29 @@ pub struct GameState {
     pub board: Board,
     red_player: Player,
     blue_player: Player,
+    #[cfg_attr(feature = "serde", serde(default))]
     undeployed_red_pieces: Vec<Piece>,
+    #[cfg_attr(feature = "serde", serde(default))]
     undeployed_blue_pieces: Vec<Piece>
 }
 
 impl GameState {
+    /// Creates a brand-new game state on the given board
+    /// with red as the starting color.
+    pub fn new(board: Board) -> Self {
+        let undeployed = |owner| INITIAL_PIECE_TYPES.iter().map(|&piece_type| Piece { piece_type, owner }).collect();
+        Self {
+            turn: 0,
+            start_player_color: PlayerColor::Red,
+            current_player_color: PlayerColor::Red,
+            board,
+            red_player: Player { color: PlayerColor::Red, display_name: "Alice".to_owned() },
+            blue_player: Player { color: PlayerColor::Blue, display_name: "Bob".to_owned() },
+            undeployed_red_pieces: undeployed(PlayerColor::Red),
+            undeployed_blue_pieces: undeployed(PlayerColor::Blue)
+        }
+    }
+
     /// Fetches the undeployed pieces for a specific color.
     pub fn undeployed_pieces(&self, color: PlayerColor) -> &Vec<Piece> {
         match color {
@@ -29,6 +48,14 @@ impl GameState {
         }
     }
     
+    /// Mutably fetches the undeployed pieces for a specific color.
+    fn undeployed_pieces_mut(&mut self, color: PlayerColor) -> &mut Vec<Piece> {
+        match color {
+            PlayerColor::Red => &mut self.undeployed_red_pieces,
+            PlayerColor::Blue => &mut self.undeployed_blue_pieces
+        }
+    }
+    
     /// Fetches the player data for a given color.
     pub fn player(&self, color: PlayerColor) -> &Player {
         match color {
@@ -68,7 +95,7 @@ impl GameState {
             Err("Grasshopper can only move along straight lines".into())
         } else if start.is_adjacent_to(destination) {
             Err("Grasshopper must not move to a neighbor".into())
-        } else if start.line_iter(destination).map(|c| AxialCoords::from(c)).any(|c| self.board.field(c).map(|f| f.is_empty()).unwrap_or(false)) {
+        } else if start.line_iter(destination).map(AxialCoords::from).any(|c| self.board.field(c).map(|f| f.is_empty()).unwrap_or(false)) {
             Err("Grasshopper cannot move over empty fields".into())
         } else {
             Ok(())
@@ -99,7 +126,7 @@ impl GameState {
             Err("Piece is not undeployed".into())
         } else if !self.board.neighbors(destination).any(|(_, f)| f.is_owned_by(color)) {
             Err("Piece is not placed next to an own piece".into())
-        } else if self.board.neighbors(destination).any(|(_, f)| f.is_owned_by(color)) {
+        } else if self.board.is_next_to(color.opponent(), destination) {
             Err("Piece must not be placed next to an opponent's piece".into())
         } else {
             Ok(())
@@ -122,12 +149,10 @@ impl GameState {
                 Err("Cannot move when start == destination".into())
             } else if self.board.field(destination).and_then(|f| f.piece()).map(|p| p.piece_type == PieceType::Beetle).unwrap_or(false) {
                 Err("Only beetles can climb other pieces".into())
-            } else if {
-                let mut without_piece = self.board.clone();
-                without_piece.field_mut(start).ok_or_else(|| "Start field does not exist")?.pop();
-                !without_piece.is_swarm_connected()
-            } {
+            } else if self.would_disconnect_swarm(start)? {
                 Err("Drag move would disconnect the swarm".into())
+            } else if !self.touches_swarm_after_lift(start, destination)? {
+                Err("Drag move destination has to be next to the swarm".into())
             } else {
                 match dragged_piece.piece_type {
                     PieceType::Ant => self.validate_ant_move(start, destination),
@@ -142,6 +167,22 @@ impl GameState {
         }
     }
     
+    /// Tests whether lifting the top-most piece at the
+    /// given coordinates would disconnect the swarm.
+    fn would_disconnect_swarm(&self, start: AxialCoords) -> SCResult<bool> {
+        let mut without_piece = self.board.clone();
+        without_piece.field_mut(start).ok_or("Start field does not exist")?.pop();
+        Ok(!without_piece.is_swarm_connected())
+    }
+    
+    /// Tests whether the destination still touches the swarm
+    /// after lifting the top-most piece at the start.
+    fn touches_swarm_after_lift(&self, start: AxialCoords, destination: AxialCoords) -> SCResult<bool> {
+        let mut without_piece = self.board.clone();
+        without_piece.field_mut(start).ok_or("Start field does not exist")?.pop();
+        Ok(without_piece.is_next_to_piece(destination) || without_piece.field(destination).map(|f| f.has_pieces()).unwrap_or(false))
+    }
+    
     //// Tests whether the given move is valid.
     pub fn validate_move(&self, color: PlayerColor, game_move: &Move) -> SCResult<()> {
         match game_move {
@@ -150,6 +191,47 @@ impl GameState {
         }
     }
     
+    /// Performs the given move for the current player.
+    pub fn perform_move(&mut self, game_move: Move) -> SCResult<()> {
+        let color = self.current_player_color;
+
+        #[cfg(debug_assertions)]
+        self.validate_move(color, &game_move)?;
+
+        match game_move {
+            Move::SetMove { piece, destination } => {
+                let undeployed = self.undeployed_pieces_mut(color);
+                let index = undeployed.iter().position(|&p| p == piece).ok_or("Piece is not undeployed")?;
+                undeployed.remove(index);
+                self.board.field_mut(destination.coords).ok_or("Move destination does not exist")?.push(piece);
+            },
+            Move::DragMove { start, destination } => {
+                let piece = self.board.field_mut(start.coords).and_then(|f| f.pop()).ok_or("No piece to move")?;
+                self.board.field_mut(destination.coords).ok_or("Move destination does not exist")?.push(piece);
+            }
+        }
+
+        self.turn += 1;
+        self.current_player_color = color.opponent();
+        Ok(())
+    }
+    
+    /// Whether the game has ended, i.e. a bee has been
+    /// surrounded or the round limit has been reached.
+    pub fn is_game_over(&self) -> bool {
+        self.board.is_bee_surrounded(PlayerColor::Red)
+            || self.board.is_bee_surrounded(PlayerColor::Blue)
+            || self.round() as usize >= ROUND_LIMIT
+    }
+    
+    /// Computes the points of the given color, which are the
+    /// number of blocked fields around the opponent's bee.
+    pub fn points(&self, color: PlayerColor) -> i32 {
+        self.board.bee_coords(color.opponent())
+            .map(|c| self.board.blocked_neighbor_count(c) as i32)
+            .unwrap_or(0)
+    }
+    
     /// Fetches a list of possible `SetMove`s.
     fn possible_set_moves(&self, color: PlayerColor) -> Vec<Move> {
         trace!("Finding possible SetMoves");
@@ -217,6 +299,29 @@ impl GameState {
         }).collect()
     }
     
+    /// Infers the move leading from this state to the given
+    /// successor from the changed piece stacks and the piece
+    /// that is no longer undeployed.
+    pub fn infer_move(&self, next: &GameState) -> Option<Move> {
+        let color = self.current_player_color;
+        let stack_change = |coords: AxialCoords| next.board.field(coords).map_or(0, |f| f.piece_stack().len() as i32)
+            - self.board.field(coords).map_or(0, |f| f.piece_stack().len() as i32);
+        let positioned = |(coords, field): (AxialCoords, &Field)| PositionedField { coords, field: field.clone() };
+
+        let destination = self.board.fields().find(|&(c, _)| stack_change(c) > 0).map(positioned)?;
+        let count = |pieces: &Vec<Piece>, piece: &Piece| pieces.iter().filter(|&p| p == piece).count();
+        let set_piece = self.undeployed_pieces(color).iter()
+            .find(|&p| count(self.undeployed_pieces(color), p) > count(next.undeployed_pieces(color), p));
+
+        match set_piece {
+            Some(&piece) => Some(Move::SetMove { piece, destination }),
+            None => {
+                let start = self.board.fields().find(|&(c, _)| stack_change(c) < 0).map(positioned)?;
+                Some(Move::DragMove { start, destination })
+            }
+        }
+    }
+    
     /// Fetches a list of possible moves for a given color.
     pub fn possible_moves(&self, color: PlayerColor) -> Vec<Move> {
         trace!("Finding possible moves for color {:?}", color);
@@ -231,9 +336,9 @@ impl GameState {
 impl FromXmlNode for GameState {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
         Ok(Self {
-            turn: node.attribute("turn")?.parse()?,
-            start_player_color: node.attribute("startPlayerColor")?.parse()?,
-            current_player_color: node.attribute("currentPlayerColor")?.parse()?,
+            turn: node.parse_attribute("turn")?,
+            start_player_color: node.parse_attribute("startPlayerColor")?,
+            current_player_color: node.parse_attribute("currentPlayerColor")?,
             red_player: Player::from_node(node.child_by_name("red")?)?,
             blue_player: Player::from_node(node.child_by_name("blue")?)?,
             board: Board::from_node(node.child_by_name("board")?)?,
@@ -242,3 +347,19 @@ impl FromXmlNode for GameState {
         })
     }
 }
+
+impl From<GameState> for XmlNode {
+    fn from(state: GameState) -> Self {
+        XmlNode::new("state")
+            .attribute("class", "state")
+            .attribute("turn", state.turn.to_string())
+            .attribute("startPlayerColor", state.start_player_color)
+            .attribute("currentPlayerColor", state.current_player_color)
+            .child(XmlNodeBuilder::from(state.red_player).name("red"))
+            .child(XmlNodeBuilder::from(state.blue_player).name("blue"))
+            .child(state.board)
+            .child(XmlNode::new("undeployedRedPieces").childs(state.undeployed_red_pieces.into_iter().map(XmlNode::from)))
+            .child(XmlNode::new("undeployedBluePieces").childs(state.undeployed_blue_pieces.into_iter().map(XmlNode::from)))
+            .build()
+    }
+}
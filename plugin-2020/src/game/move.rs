@@ -1,4 +1,4 @@
-use socha_client_base::xml_node::{XmlNode, XmlNodeBuilder};
+use socha_client_base::{util::SCResult, xml_node::{FromXmlNode, XmlNode, XmlNodeBuilder}};
 
 use crate::util::AxialCoords;
 
@@ -6,11 +6,29 @@ use super::{Piece, PositionedField};
 
 /// A transition between two game states.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Move<C=AxialCoords> {
     SetMove { piece: Piece, destination: PositionedField<C> },
     DragMove { start: PositionedField<C>, destination: PositionedField<C> }
 }
 
+impl FromXmlNode for Move {
+    fn from_node(node: &XmlNode) -> SCResult<Self> {
+        let class = node.attribute("class")?;
+        match class {
+            "setmove" => Ok(Self::SetMove {
+                piece: Piece::from_node(node.child_by_name("piece")?)?,
+                destination: PositionedField::from_node(node.child_by_name("destination")?)?
+            }),
+            "dragmove" => Ok(Self::DragMove {
+                start: PositionedField::from_node(node.child_by_name("start")?)?,
+                destination: PositionedField::from_node(node.child_by_name("destination")?)?
+            }),
+            _ => Err(node.unknown_class(class))
+        }
+    }
+}
+
 impl From<Move> for XmlNode {
     fn from(game_move: Move) -> Self {
         match game_move {
@@ -4,6 +4,7 @@ use super::{PieceType, PlayerColor};
 
 /// A game piece.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
     pub owner: PlayerColor,
     pub piece_type: PieceType
@@ -12,8 +13,8 @@ pub struct Piece {
 impl FromXmlNode for Piece {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
         Ok(Self {
-            owner: node.attribute("owner")?.parse()?,
-            piece_type: node.attribute("type")?.parse()?
+            owner: node.parse_attribute("owner")?,
+            piece_type: node.parse_attribute("type")?
         })
     }
 }
@@ -4,6 +4,7 @@ use socha_client_base::{error::SCError, util::SCResult};
 
 /// A game piece type.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceType {
     Ant,
     Bee,
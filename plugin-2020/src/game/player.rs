@@ -1,9 +1,10 @@
-use socha_client_base::{util::SCResult, xml_node::FromXmlNode, xml_node::XmlNode};
+use socha_client_base::{util::SCResult, xml_node::{FromXmlNode, XmlNode, XmlNodeBuilder}};
 
 use super::PlayerColor;
 
 /// Metadata about a player.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Player {
     pub color: PlayerColor,
     pub display_name: String
@@ -12,8 +13,16 @@ pub struct Player {
 impl FromXmlNode for Player {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
         Ok(Self {
-            color: node.attribute("color")?.parse()?,
+            color: node.parse_attribute("color")?,
             display_name: node.attribute("displayName")?.to_owned()
         })
     }
 }
+
+impl<'a> From<Player> for XmlNodeBuilder<'a> {
+    fn from(player: Player) -> Self {
+        XmlNode::new("player")
+            .attribute("color", player.color)
+            .attribute("displayName", player.display_name)
+    }
+}
@@ -1,9 +1,10 @@
-use std::{convert::TryFrom, str::FromStr};
+use std::{convert::TryFrom, fmt, str::FromStr};
 
 use socha_client_base::{error::SCError, util::HasOpponent, util::SCResult};
 
 /// A player color in the game.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PlayerColor {
     Red,
     Blue
@@ -59,3 +60,9 @@ impl From<PlayerColor> for String {
         }.to_owned()
     }
 }
+
+impl fmt::Display for PlayerColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", String::from(*self))
+    }
+}
@@ -1,4 +1,4 @@
-use socha_client_base::xml_node::{XmlNode, XmlNodeBuilder};
+use socha_client_base::{util::SCResult, xml_node::{FromXmlNode, XmlNode, XmlNodeBuilder}};
 
 use crate::util::{AxialCoords, CubeCoords};
 
@@ -9,11 +9,25 @@ use super::Field;
 /// If ownership over the field is not desired, you should
 /// use a tuple instead.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PositionedField<C=AxialCoords> {
     pub field: Field,
     pub coords: C
 }
 
+impl FromXmlNode for PositionedField {
+    fn from_node(node: &XmlNode) -> SCResult<Self> {
+        Ok(Self {
+            field: Field::from_node(node)?,
+            coords: CubeCoords::new(
+                node.parse_attribute("x")?,
+                node.parse_attribute("y")?,
+                node.parse_attribute("z")?
+            ).into()
+        })
+    }
+}
+
 impl<'a, C> From<PositionedField<C>> for XmlNodeBuilder<'a> where C: Into<CubeCoords> {
     fn from(field: PositionedField<C>) -> Self {
         let cube_coords = field.coords.into();
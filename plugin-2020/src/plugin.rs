@@ -1,4 +1,4 @@
-use socha_client_base::plugin::{SCPlugin, HasPlayerColor, HasTurn};
+use socha_client_base::{plugin::{SCPlugin, HasPlayerColor, HasRules, HasTurn}, util::SCResult};
 use crate::game;
 
 /// The concrete plugin for the "Hive" game.
@@ -12,6 +12,14 @@ impl SCPlugin for SCPlugin2020 {
     type Move = game::Move;
     
     fn protocol_game_type<'a>() -> &'a str { "swc_2020_hive" }
+    
+    fn player_colors() -> Vec<game::PlayerColor> { vec![game::PlayerColor::Red, game::PlayerColor::Blue] }
+}
+
+impl HasPlayerColor for game::Player {
+    type PlayerColor = game::PlayerColor;
+    
+    fn player_color(&self) -> game::PlayerColor { self.color }
 }
 
 impl HasPlayerColor for game::GameState {
@@ -23,3 +31,19 @@ impl HasPlayerColor for game::GameState {
 impl HasTurn for game::GameState {
     fn turn(&self) -> u32 { self.turn }
 }
+
+impl HasRules for game::GameState {
+    type Move = game::Move;
+
+    fn possible_moves(&self) -> Vec<game::Move> { self.possible_moves(self.current_player_color) }
+
+    fn validate_move(&self, game_move: &game::Move) -> SCResult<()> { self.validate_move(self.current_player_color, game_move) }
+
+    fn perform_move(&mut self, game_move: game::Move) -> SCResult<()> { game::GameState::perform_move(self, game_move) }
+
+    fn infer_move(&self, next: &Self) -> Option<game::Move> { game::GameState::infer_move(self, next) }
+    
+    fn is_game_over(&self) -> bool { game::GameState::is_game_over(self) }
+
+    fn points(&self, color: game::PlayerColor) -> i32 { game::GameState::points(self, color) }
+}
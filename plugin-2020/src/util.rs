@@ -12,6 +12,7 @@ use socha_client_base::hashmap;
 /// See https://www.redblobgames.com/grids/hexagons/#coordinates-axial
 /// for a description.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AxialCoords {
     x: i32,
     y: i32
@@ -81,13 +82,14 @@ pub trait LineFormable {
 
 pub trait Adjacentable {
     /// Tests whether `self` and `rhs` are neighbors.
+    #[allow(clippy::wrong_self_convention)]
     fn is_adjacent_to(self, rhs: Self) -> bool;
 }
 
 impl AxialCoords {
     /// Creates new axial coordinates.
     #[inline]
-    pub fn new(x: i32, y: i32) -> Self { Self { x: x, y: y } }
+    pub fn new(x: i32, y: i32) -> Self { Self { x, y } }
     
     /// Fetches the x-coordinate
     #[inline]
@@ -116,14 +118,14 @@ impl CubeCoords {
     /// Creates new (unvalidated) cube coordinates.
     #[inline]
     pub fn new(x: i32, y: i32, z: i32) -> Self {
-        Self { x: x, y: y, z: z }
+        Self { x, y, z }
     }
 
     /// Creates new cube coordinates if they are valid.
     #[inline]
     pub fn new_valid(x: i32, y: i32, z: i32) -> Option<Self> {
         if (x + y + z) == 0 {
-            Some(CubeCoords { x: x, y: y, z: z })
+            Some(CubeCoords { x, y, z })
         } else {
             None
         }
@@ -146,7 +148,7 @@ impl DoubledCoords {
     /// Creates new doubled coordinates.
     #[inline]
     pub fn new(x: i32, y: i32) -> Self {
-        Self { x: x, y: y }
+        Self { x, y }
     }
 
     /// Fetches the x-coordinate
@@ -194,7 +196,7 @@ impl<C> LineFormable for C where C: Into<CubeCoords> {
 
 impl LineIter {
     pub fn new(start: CubeCoords, step: CubeCoords, destination: CubeCoords) -> Self {
-        Self { current: start, step: step, destination: destination }
+        Self { current: start, step, destination }
     }
 }
 
@@ -216,7 +218,7 @@ impl<C> Adjacentable for C where C: Into<AxialCoords> {
     fn is_adjacent_to(self, rhs: Self) -> bool {
         let lhs_axial = self.into();
         let rhs_axial = rhs.into();
-        lhs_axial.coord_neighbors().iter().any(|&c| c == rhs_axial)
+        lhs_axial.coord_neighbors().contains(&rhs_axial)
     }
 }
 
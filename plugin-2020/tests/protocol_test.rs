@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use xml::reader::EventReader;
+use socha_client_base::error::SCError;
+use socha_client_base::protocol::{Data, GameResult, Joined, Left, PlayerScore, Room, ScoreAggregation, ScoreCause, ScoreDefinition, ScoreFragment};
+use socha_client_base::xml_node::{FromXmlNode, XmlNode};
+use socha_plugin_2020::game::{Board, GameState, Move, PieceType, PlayerColor, BOARD_RADIUS};
+use socha_plugin_2020::plugin::SCPlugin2020;
+
+/// Serializes the node to a string and parses it back.
+fn reparse(node: XmlNode) -> XmlNode {
+    let xml = node.to_string();
+    XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).expect("Could not reparse node")
+}
+
+fn assert_room_round_trip(data: Data<SCPlugin2020>) {
+    let room = Room::<SCPlugin2020> { room_id: "abc".to_owned(), data };
+    let node = reparse(XmlNode::try_from(room.clone()).expect("Could not serialize room"));
+    assert_eq!(<Room<SCPlugin2020>>::from_node(&node).expect("Could not parse room"), room);
+}
+
+#[test]
+pub fn test_data_round_trip() {
+    let state = GameState::new(Board::filling_radius(BOARD_RADIUS, HashMap::new()));
+    let game_move = state.possible_moves(PlayerColor::Red).into_iter()
+        .find(|m| matches!(m, Move::SetMove { piece, .. } if piece.piece_type == PieceType::Bee))
+        .expect("No bee move available");
+    let result = GameResult::<SCPlugin2020> {
+        definition: ScoreDefinition {
+            fragments: vec![ScoreFragment { name: "Siegpunkte".to_owned(), aggregation: ScoreAggregation::Sum, relevant_for_ranking: true }]
+        },
+        scores: vec![
+            PlayerScore { cause: ScoreCause::Regular, reason: String::new(), parts: vec![2.0] },
+            PlayerScore { cause: ScoreCause::SoftTimeout, reason: "Too slow".to_owned(), parts: vec![0.0] }
+        ],
+        winners: vec![state.player(PlayerColor::Red).clone()]
+    };
+
+    assert_room_round_trip(Data::WelcomeMessage { color: PlayerColor::Blue });
+    assert_room_round_trip(Data::Memento { state });
+    assert_room_round_trip(Data::Move(game_move));
+    assert_room_round_trip(Data::MoveRequest);
+    assert_room_round_trip(Data::GameResult(result));
+    assert_room_round_trip(Data::Error { message: "Something went wrong".to_owned() });
+}
+
+#[test]
+pub fn test_game_result_scores() {
+    let xml = r#"
+        <data class="result">
+            <definition>
+                <fragment name="Siegpunkte"><aggregation>SUM</aggregation><relevantForRanking>true</relevantForRanking></fragment>
+                <fragment name="∅ Punkte"><aggregation>AVERAGE</aggregation><relevantForRanking>true</relevantForRanking></fragment>
+            </definition>
+            <score cause="REGULAR" reason=""><part>0</part><part>3.5</part></score>
+            <score cause="REGULAR" reason=""><part>2</part><part>5</part></score>
+            <winner color="BLUE" displayName="Blue" />
+        </data>
+    "#;
+    let node = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+    let result = GameResult::<SCPlugin2020>::from_node(&node).unwrap();
+
+    let blue = result.score(PlayerColor::Blue).unwrap();
+    assert_eq!(blue.parts, vec![2.0, 5.0]);
+    let values: Vec<_> = blue.values(&result.definition).map(|(f, v)| (f.name.as_str(), v)).collect();
+    assert_eq!(values, vec![("Siegpunkte", 2.0), ("∅ Punkte", 5.0)]);
+
+    assert!(result.is_winner(PlayerColor::Blue));
+    assert!(!result.is_winner(PlayerColor::Red));
+    assert!(!result.is_draw());
+    assert_eq!(result.rank(PlayerColor::Blue), Some(1));
+    assert_eq!(result.rank(PlayerColor::Red), Some(2));
+
+    let aggregated = result.definition.aggregate(result.scores.iter());
+    assert_eq!(aggregated, vec![2.0, 4.25]);
+    assert_eq!(ScoreAggregation::Average.aggregate(Vec::new()), 0.0);
+}
+
+#[test]
+pub fn test_joined_left_round_trip() {
+    let joined = Joined { room_id: "abc".to_owned(), game_type: "swc_2020_hive".to_owned() };
+    assert_eq!(Joined::from_node(&reparse(joined.clone().into())).unwrap(), joined);
+
+    let node = XmlNode::read_from(&mut EventReader::new(r#"<joined roomId="abc" />"#.as_bytes())).unwrap();
+    assert_eq!(Joined::from_node(&node).unwrap().game_type, "");
+
+    let left = Left { room_id: "abc".to_owned() };
+    assert_eq!(Left::from_node(&reparse(left.clone().into())).unwrap(), left);
+}
+
+#[test]
+pub fn test_unknown_data_class() {
+    let xml = r#"<room roomId="abc"><data class="unknown" /></room>"#;
+    let node = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+    match <Room<SCPlugin2020>>::from_node(&node) {
+        Err(SCError::UnknownDataClass { path, class }) => {
+            assert_eq!(path, "room/data[0]");
+            assert_eq!(class, "unknown");
+        },
+        result => panic!("Expected an unknown data class, got {:?}", result)
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::iter::once;
+use socha_client_base::error::SCError;
+use socha_client_base::plugin::{HasPlayerColor, HasRules};
+use socha_client_base::util::HasOpponent;
+use socha_plugin_2020::game::{Board, Field, GameState, Move, Piece, PieceType, PlayerColor, PositionedField, BOARD_RADIUS, INITIAL_PIECE_TYPES};
+use socha_plugin_2020::util::AxialCoords;
+
+fn board_with(pieces: Vec<(AxialCoords, Vec<Piece>)>) -> Board {
+    Board::filling_radius(BOARD_RADIUS, pieces.into_iter()
+        .map(|(c, stack)| (c, Field::new(stack, false)))
+        .collect::<HashMap<_, _>>())
+}
+
+#[test]
+fn test_shared_neighbors_with_single_pieces() {
+    let bee = Piece { owner: PlayerColor::Red, piece_type: PieceType::Bee };
+    let board = board_with(vec![(AxialCoords::new(0, 1), vec![bee])]);
+    let shared: Vec<_> = board.shared_neighbors(AxialCoords::new(0, 0), AxialCoords::new(1, 0), None).into_iter().map(|(c, _)| c).collect();
+    assert!(shared.contains(&AxialCoords::new(0, 1)));
+
+    // Only the exception is left out
+    let shared: Vec<_> = board.shared_neighbors(AxialCoords::new(0, 0), AxialCoords::new(1, 0), Some(AxialCoords::new(0, 1))).into_iter().map(|(c, _)| c).collect();
+    assert_eq!(shared, vec![AxialCoords::new(1, -1)]);
+}
+
+#[test]
+fn test_set_move_next_to_opponent() {
+    let piece = |owner, piece_type| Piece { owner, piece_type };
+    let state = GameState::new(board_with(vec![
+        (AxialCoords::new(0, 0), vec![piece(PlayerColor::Red, PieceType::Bee)]),
+        (AxialCoords::new(1, 0), vec![piece(PlayerColor::Blue, PieceType::Bee)])
+    ]));
+    let set_move = |x, y| Move::SetMove {
+        piece: piece(PlayerColor::Red, PieceType::Ant),
+        destination: PositionedField { coords: AxialCoords::new(x, y), field: Field::new(once(piece(PlayerColor::Red, PieceType::Ant)), false) }
+    };
+    assert!(state.validate_move(PlayerColor::Red, &set_move(-1, 0)).is_ok());
+    assert!(state.validate_move(PlayerColor::Red, &set_move(0, 1)).is_err());
+}
+
+#[test]
+fn test_drag_move_along_itself() {
+    let piece = |owner, piece_type| Piece { owner, piece_type };
+    let state = GameState::new(board_with(vec![
+        (AxialCoords::new(0, 0), vec![piece(PlayerColor::Red, PieceType::Bee), piece(PlayerColor::Blue, PieceType::Beetle)]),
+        (AxialCoords::new(1, 0), vec![piece(PlayerColor::Red, PieceType::Ant)])
+    ]));
+    let positioned = |x, y| PositionedField { coords: AxialCoords::new(x, y), field: Field::new(once(piece(PlayerColor::Red, PieceType::Ant)), false) };
+    // The destination only touches the ant's own start field
+    let result = state.validate_move(PlayerColor::Red, &Move::DragMove { start: positioned(1, 0), destination: positioned(2, -1) });
+    assert!(matches!(result, Err(SCError::Custom(ref message)) if message == "Drag move destination has to be next to the swarm"));
+}
+
+/// Fetches the distance of the coordinates to the center.
+fn center_distance(coords: AxialCoords) -> i32 {
+    (coords.x().abs() + coords.y().abs() + (coords.x() + coords.y()).abs()) / 2
+}
+
+#[test]
+fn test_performing_moves() {
+    let mut state = GameState::new(Board::filling_radius(BOARD_RADIUS, HashMap::new()));
+    assert_eq!(state.player_color(), PlayerColor::Red);
+    assert!(!state.is_game_over());
+
+    for turn in 0..8 {
+        let color = state.player_color();
+        let moves = HasRules::possible_moves(&state);
+        assert!(!moves.is_empty());
+        assert!(moves.iter().all(|m| HasRules::validate_move(&state, m).is_ok()));
+
+        // Only set moves deploy a piece each turn. The one closest
+        // to the center is performed, since pieces at the edge
+        // may leave no set move afterwards and the order of
+        // the possible moves is unspecified
+        let set_move = moves.into_iter()
+            .filter_map(|m| match m {
+                Move::SetMove { ref destination, .. } => Some((center_distance(destination.coords), destination.coords, m)),
+                _ => None
+            })
+            .min_by_key(|&(distance, coords, _)| (distance, coords))
+            .map(|(_, _, m)| m)
+            .expect("No set move available");
+        state.perform_move(set_move).unwrap();
+        assert_eq!(state.turn, turn + 1);
+        assert_eq!(state.player_color(), color.opponent());
+    }
+
+    // After four rounds, both bees have to be placed
+    for &color in &[PlayerColor::Red, PlayerColor::Blue] {
+        assert!(state.board.has_placed_bee(color));
+        assert_eq!(state.undeployed_pieces(color).len(), INITIAL_PIECE_TYPES.len() - 4);
+    }
+}
+
+#[test]
+fn test_surrounded_bee() {
+    let ascii_hex = r#"    /\  /\    
+   /  \/  \   
+   |BA |BA |  
+  /\  /\  /\  
+ /  \/  \/  \ 
+ |BG |RB |BS |
+ \  /\  /\  / 
+  \/  \/  \/  
+   |BT |BA |  
+   \  /\  /   
+    \/  \/    "#;
+    let state = GameState::new(Board::from_ascii_hex_grid(ascii_hex).unwrap());
+    assert!(state.board.is_bee_surrounded(PlayerColor::Red));
+    assert!(state.is_game_over());
+    assert_eq!(state.points(PlayerColor::Blue), 6);
+    assert_eq!(state.points(PlayerColor::Red), 0);
+    assert_eq!(state.winner(), Some(PlayerColor::Blue));
+}
+
+#[test]
+fn test_drag_move_stays_connected() {
+    let piece = |owner, piece_type| Field::new(once(Piece { owner, piece_type }), false);
+    let board = Board::filling_radius(BOARD_RADIUS, vec![
+        (AxialCoords::new(0, 0), piece(PlayerColor::Red, PieceType::Ant)),
+        (AxialCoords::new(1, 0), piece(PlayerColor::Red, PieceType::Bee)),
+        (AxialCoords::new(2, 0), piece(PlayerColor::Blue, PieceType::Bee))
+    ].into_iter().collect::<HashMap<_, _>>());
+    let state = GameState::new(board);
+    let drag_moves: Vec<_> = HasRules::possible_moves(&state).into_iter()
+        .filter(|m| matches!(m, Move::DragMove { .. }))
+        .collect();
+    assert!(!drag_moves.is_empty());
+    for game_move in drag_moves {
+        assert!(state.after_move(game_move).unwrap().board.is_swarm_connected());
+    }
+}
+
+#[test]
+fn test_inferring_moves() {
+    let mut state = GameState::new(Board::filling_radius(BOARD_RADIUS, HashMap::new()));
+    let mut drag_moves = 0;
+
+    for _ in 0..16 {
+        let moves = HasRules::possible_moves(&state);
+        let game_move = moves.iter().find(|m| matches!(m, Move::DragMove { .. })).unwrap_or(&moves[0]).clone();
+        drag_moves += matches!(game_move, Move::DragMove { .. }) as usize;
+
+        let next = state.after_move(game_move.clone()).unwrap();
+        assert_eq!(state.infer_move(&next), Some(game_move));
+        state = next;
+    }
+
+    assert!(drag_moves > 0);
+    assert_eq!(state.infer_move(&state.clone()), None);
+}
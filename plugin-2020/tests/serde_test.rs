@@ -0,0 +1,39 @@
+#![cfg(feature = "serde")]
+
+use std::collections::HashMap;
+use socha_client_base::xml_serde;
+use socha_plugin_2020::game::{Board, GameState, Move, BOARD_RADIUS};
+
+fn played_state() -> GameState {
+    let mut state = GameState::new(Board::filling_radius(BOARD_RADIUS, HashMap::new()));
+    for _ in 0..2 {
+        let game_move = state.possible_moves(state.current_player_color).into_iter().next().expect("No move available");
+        state.perform_move(game_move).expect("Could not perform move");
+    }
+    state
+}
+
+#[test]
+fn test_game_state_json_round_trip() {
+    let state = played_state();
+    let json = serde_json::to_string(&state).unwrap();
+    assert_eq!(serde_json::from_str::<GameState>(&json).unwrap(), state);
+}
+
+#[test]
+fn test_move_json_round_trip() {
+    let state = played_state();
+    for game_move in state.possible_moves(state.current_player_color).into_iter().take(3) {
+        let json = serde_json::to_string(&game_move).unwrap();
+        assert_eq!(serde_json::from_str::<Move>(&json).unwrap(), game_move);
+    }
+}
+
+#[test]
+fn test_game_state_xml_round_trip() {
+    let state = played_state();
+    let node = xml_serde::to_node("state", &state).unwrap();
+    assert_eq!(node.name(), "state");
+    assert_eq!(node.attribute("turn").unwrap(), "2");
+    assert_eq!(xml_serde::from_node::<GameState>(&node).unwrap(), state);
+}
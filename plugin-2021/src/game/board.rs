@@ -6,8 +6,10 @@ pub const BOARD_SIZE: usize = 20;
 
 /// The game board is a 20x20 grid of fields with colors.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Board {
     // TODO: More efficient representation, e.g. using a 2D matrix of colors
+    #[cfg_attr(feature = "serde", serde(default))]
     fields: Vec<Field>
 }
 
@@ -109,6 +111,12 @@ impl Board {
     }
 }
 
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FromXmlNode for Board {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
         Ok(Self {
@@ -116,3 +124,11 @@ impl FromXmlNode for Board {
         })
     }
 }
+
+impl From<Board> for XmlNode {
+    fn from(board: Board) -> Self {
+        XmlNode::new("board")
+            .childs(board.fields.into_iter().map(XmlNode::from))
+            .build()
+    }
+}
@@ -5,8 +5,10 @@ use socha_client_base::{error::SCError, util::SCResult, xml_node::FromXmlNode, x
 use super::Team;
 
 /// A color in the game.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
+    #[default]
     None,
     Blue,
     Yellow,
@@ -38,12 +40,6 @@ impl Color {
     }
 }
 
-impl Default for Color {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 impl FromStr for Color {
     type Err = SCError;
 
@@ -72,6 +68,12 @@ impl fmt::Display for Color {
 
 impl FromXmlNode for Color {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
-        node.content().parse()
+        node.parse_content()
+    }
+}
+
+impl From<Color> for XmlNode {
+    fn from(color: Color) -> Self {
+        XmlNode::new("color").content(color.to_string().as_str()).build()
     }
 }
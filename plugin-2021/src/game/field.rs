@@ -4,6 +4,7 @@ use super::{Color, Vec2};
 
 /// A field on the board holding a color.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Field {
     pub position: Vec2,
     pub content: Color
@@ -13,10 +14,20 @@ impl FromXmlNode for Field {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
         Ok(Self {
             position: Vec2::new(
-                node.attribute("x")?.parse()?,
-                node.attribute("y")?.parse()?
+                node.parse_attribute("x")?,
+                node.parse_attribute("y")?
             ),
-            content: node.attribute("content")?.parse()?
+            content: node.parse_attribute("content")?
         })
     }
 }
+
+impl From<Field> for XmlNode {
+    fn from(field: Field) -> Self {
+        XmlNode::new("field")
+            .attribute("x", field.position.x.to_string())
+            .attribute("y", field.position.y.to_string())
+            .attribute("content", field.content.to_string())
+            .build()
+    }
+}
@@ -1,12 +1,13 @@
 use std::{collections::{HashMap, HashSet}, iter::once};
 
-use socha_client_base::{util::SCResult, xml_node::{FromXmlNode, XmlNode}};
+use socha_client_base::{util::SCResult, xml_node::{FromXmlNode, XmlNode, XmlNodeBuilder}};
 
 use super::{BOARD_SIZE, Board, CORNERS, Color, Move, PIECE_SHAPES, PIECE_SHAPES_BY_NAME, Piece, PieceShape, Player, Team, Vec2};
 
 /// A snapshot of the game's state. It holds the
 /// information needed to compute the next move.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameState {
     /// The number of already committed moves.
     pub turn: u32,
@@ -25,23 +26,31 @@ pub struct GameState {
     /// The team that begins the game.
     pub start_team: Team,
     /// A list of all colors currently in the game.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub ordered_colors: Vec<Color>,
     /// A map that stores, for each color, whether the last move was a monomino if all pieces have been placed.
     pub last_move_mono: HashMap<Color, bool>,
     /// The current color's index
     pub current_color_index: u32,
     /// The undeployed blue shapes.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub blue_shapes: HashSet<PieceShape>,
     /// The undeployed yellow shapes.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub yellow_shapes: HashSet<PieceShape>,
     /// The undeployed red shapes.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub red_shapes: HashSet<PieceShape>,
     /// The undeployed green shapes.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub green_shapes: HashSet<PieceShape>
 }
 
 const SUM_MAX_SQUARES: i32 = 89;
 
+/// The number of rounds after which the game ends.
+pub const ROUND_LIMIT: u32 = 25;
+
 impl GameState {
     /// Creates a brand-new game state with blue as the starting color
     /// and team one as the starting team. Mostly for debugging purposes.
@@ -65,9 +74,10 @@ impl GameState {
         }
     }
 
-    /// Fetches the current color.
+    /// Fetches the current color, which is `None`
+    /// once no color is left in the game.
     pub fn current_color(&self) -> Color {
-        self.ordered_colors[self.current_color_index as usize]
+        self.ordered_colors.get(self.current_color_index as usize).cloned().unwrap_or(Color::None)
     }
 
     /// Fetches the current team.
@@ -128,6 +138,22 @@ impl GameState {
         self.undeployed_shapes_of_color(self.current_color()).count() == PIECE_SHAPES.len()
     }
 
+    /// Whether the game has ended.
+    pub fn is_game_over(&self) -> bool {
+        self.ordered_colors.is_empty() || self.round > ROUND_LIMIT
+    }
+
+    /// Computes the points scored by the given team so far.
+    pub fn points(&self, team: Team) -> i32 {
+        [Color::Blue, Color::Yellow, Color::Red, Color::Green].iter()
+            .filter(|c| c.team() == team)
+            .map(|&c| Self::get_points_from_undeployed(
+                self.undeployed_shapes_of_color(c).cloned().collect(),
+                self.last_move_mono.get(&c).cloned().unwrap_or(false)
+            ))
+            .sum()
+    }
+
     /// Performs the given move.
     pub fn perform_move(&mut self, game_move: Move) -> SCResult<()> {
         #[cfg(debug_assertions)]
@@ -146,6 +172,20 @@ impl GameState {
         Ok(s)
     }
 
+    /// Checks whether the given move is valid.
+    pub fn validate_move(&self, game_move: &Move) -> SCResult<()> {
+        self.validate_move_color(game_move)?;
+
+        match game_move {
+            Move::Set { piece } => self.validate_set_move(piece),
+            Move::Skip { .. } => if self.is_first_move() {
+                Err("Cannot skip the first round!".into())
+            } else {
+                self.validate_skip()
+            }
+        }
+    }
+
     /// Checks whether the given move has the right color.
     fn validate_move_color(&self, game_move: &Move) -> SCResult<()> {
         if game_move.color() != self.current_color() {
@@ -188,7 +228,7 @@ impl GameState {
 
         if self.is_first_move() {
             // Check whether it is placed correctly in a corner
-            if !piece.coordinates().any(|p| Board::is_on_corner(p)) {
+            if !piece.coordinates().any(Board::is_on_corner) {
                 return Err("The piece from the set move is not located in a corner!".into());
             }
         } else {
@@ -206,15 +246,38 @@ impl GameState {
             return Err("Game has already ended, cannot advance!".into());
         }
 
-        self.current_color_index = (self.current_color_index + turns) % self.ordered_colors.len() as u32;
-        // TODO: This doesn't seem correct, but matches the implementation of https://github.com/CAU-Kiel-Tech-Inf/backend/blob/97d185660754ffba4bd4444f3f39ae350f1d053e/plugin/src/shared/sc/plugin2021/GameState.kt#L114-L123
-        // Perhaps we should divide AFTER the turns have been added, then simply assign instead of add-assign the round?
-        self.round += turns / self.ordered_colors.len() as u32;
+        let colors = self.ordered_colors.len() as u32;
+        let index = self.current_color_index + turns;
+        // A round ends once the last of the remaining colors has moved
+        self.round += index / colors;
+        self.current_color_index = index % colors;
         self.turn += turns;
 
         Ok(())
     }
 
+    /// Removes the colors that cannot place any more pieces,
+    /// beginning with the current one, until a color that
+    /// can still move is found or no color is left.
+    fn remove_stuck_colors(&mut self) {
+        while !self.ordered_colors.is_empty() && !self.can_place_piece() {
+            self.ordered_colors.remove(self.current_color_index as usize);
+            if self.current_color_index as usize >= self.ordered_colors.len() {
+                self.current_color_index = 0;
+                self.round += 1;
+            }
+        }
+    }
+
+    /// Whether the current color can place any of its pieces.
+    fn can_place_piece(&self) -> bool {
+        if self.is_first_move() {
+            self.possible_first_moves().next().is_some()
+        } else {
+            self.usual_set_pieces().next().is_some()
+        }
+    }
+
     /// Performs the given set move.
     fn perform_set_move(&mut self, piece: Piece) -> SCResult<()> {
         #[cfg(debug_assertions)]
@@ -223,7 +286,7 @@ impl GameState {
         self.board.place(&piece);
 
         let undeployed = self.undeployed_shapes_of_color_mut(piece.color);
-        undeployed.remove(&piece.shape());
+        undeployed.remove(&piece.kind);
         // TODO: Track deployed shapes
         
         // If this was the last piece for this color, remove it from the turn queue
@@ -232,6 +295,7 @@ impl GameState {
         }
 
         self.try_advance(1)?;
+        self.remove_stuck_colors();
         Ok(())
     }
 
@@ -242,6 +306,7 @@ impl GameState {
         }
 
         self.try_advance(1)?;
+        self.remove_stuck_colors();
         Ok(())
     }
 
@@ -263,15 +328,47 @@ impl GameState {
         }
     }
 
+    /// Infers the move leading from this state to the given
+    /// successor from the newly colored fields and the shape
+    /// that is no longer undeployed. No new fields indicate a skip.
+    pub fn infer_move(&self, next: &GameState) -> Option<Move> {
+        let color = self.current_color();
+        let placed: HashSet<_> = (0..BOARD_SIZE as i32)
+            .flat_map(|y| (0..BOARD_SIZE as i32).map(move |x| Vec2::new(x, y)))
+            .filter(|&p| self.board.get(p) != color && next.board.get(p) == color)
+            .collect();
+        if placed.is_empty() {
+            return Some(Move::Skip { color });
+        }
+
+        // The position is the top left corner of the bounding box
+        let position = placed.iter().fold(Vec2::both(BOARD_SIZE as i32), |m, &p| m.min(p));
+        let remaining: HashSet<_> = next.undeployed_shapes_of_color(color).collect();
+        self.undeployed_shapes_of_color(color)
+            .filter(|kind| !remaining.contains(kind) && kind.coordinates().count() == placed.len())
+            .flat_map(|kind| kind.transformations().map(move |(rotation, is_flipped)| Piece { kind: kind.clone(), rotation, is_flipped, color, position }))
+            .find(|piece| piece.coordinates().collect::<HashSet<_>>() == placed)
+            .map(|piece| Move::Set { piece })
+    }
+
     /// Fetches the possible non-start moves
     fn possible_usual_set_moves(&self) -> impl Iterator<Item=Move> {
+        self.usual_set_pieces()
+            .map(|piece| Move::Set { piece })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Lazily enumerates the valid non-start placements
+    /// of the current color's undeployed pieces.
+    fn usual_set_pieces(&self) -> impl Iterator<Item=Piece> + '_ {
         let color = self.current_color();
         self.undeployed_shapes_of_color(color)
-            .flat_map(|kind| {
+            .flat_map(move |kind| {
                 let bb = kind.bounding_box();
                 let placable = Vec2::both(BOARD_SIZE as i32 - 1) - bb;
                 kind.transformations()
-                    .flat_map(|(rotation, is_flipped)| placable
+                    .flat_map(move |(rotation, is_flipped)| placable
                         .into_iter()
                         .map(move |position| Piece {
                             kind: kind.clone(),
@@ -281,12 +378,8 @@ impl GameState {
                             position
                         })
                     )
-                    .filter(|piece| self.validate_set_move(piece).is_ok())
-                    .map(|piece| Move::Set { piece })
-                    .collect::<Vec<_>>()
             })
-            .collect::<Vec<_>>()
-            .into_iter()
+            .filter(move |piece| self.validate_set_move(piece).is_ok())
     }
 
     /// Fetches the possible start moves
@@ -317,17 +410,17 @@ impl GameState {
 impl FromXmlNode for GameState {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
         Ok(Self {
-            turn: node.attribute("turn")?.parse()?,
-            round: node.attribute("round")?.parse()?,
+            turn: node.parse_attribute("turn")?,
+            round: node.parse_attribute("round")?,
             first: Player::from_node(node.child_by_name("first")?)?,
             second: Player::from_node(node.child_by_name("second")?)?,
             board: Board::from_node(node.child_by_name("board")?)?,
-            start_piece: node.attribute("startPiece")?.parse()?,
+            start_piece: node.parse_attribute("startPiece")?,
             start_color: Color::from_node(node.child_by_name("startColor")?)?,
             start_team: Team::from_node(node.child_by_name("startTeam")?)?,
             ordered_colors: node.child_by_name("orderedColors")?.childs_by_name("color").map(Color::from_node).collect::<Result<_, _>>()?,
             last_move_mono: HashMap::new(), // TODO
-            current_color_index: node.attribute("currentColorIndex")?.parse()?,
+            current_color_index: node.parse_attribute("currentColorIndex")?,
             blue_shapes: node.child_by_name("blueShapes")?.childs_by_name("shape").map(PieceShape::from_node).collect::<Result<_, _>>()?,
             yellow_shapes: node.child_by_name("yellowShapes")?.childs_by_name("shape").map(PieceShape::from_node).collect::<Result<_, _>>()?,
             red_shapes: node.child_by_name("redShapes")?.childs_by_name("shape").map(PieceShape::from_node).collect::<Result<_, _>>()?,
@@ -336,12 +429,51 @@ impl FromXmlNode for GameState {
     }
 }
 
+impl From<GameState> for XmlNode {
+    fn from(state: GameState) -> Self {
+        XmlNode::new("state")
+            .attribute("class", "state")
+            .attribute("turn", state.turn.to_string())
+            .attribute("round", state.round.to_string())
+            .attribute("startPiece", state.start_piece.to_string())
+            .attribute("currentColorIndex", state.current_color_index.to_string())
+            .child(XmlNodeBuilder::from(state.first).name("first"))
+            .child(XmlNodeBuilder::from(state.second).name("second"))
+            .child(state.board)
+            .child(XmlNode::new("startColor").content(state.start_color.to_string().as_str()))
+            .child(XmlNode::new("startTeam").content(state.start_team.to_string().as_str()))
+            .child(XmlNode::new("orderedColors").childs(state.ordered_colors.into_iter().map(XmlNode::from)))
+            .child(XmlNode::new("blueShapes").childs(state.blue_shapes.into_iter().map(XmlNode::from)))
+            .child(XmlNode::new("yellowShapes").childs(state.yellow_shapes.into_iter().map(XmlNode::from)))
+            .child(XmlNode::new("redShapes").childs(state.red_shapes.into_iter().map(XmlNode::from)))
+            .child(XmlNode::new("greenShapes").childs(state.green_shapes.into_iter().map(XmlNode::from)))
+            .build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::game::{Color, Move, PIECE_SHAPES_BY_NAME, Team};
 
     use super::GameState;
 
+    #[test]
+    fn test_inferring_moves() {
+        let mut state = GameState::new(PIECE_SHAPES_BY_NAME["PENTO_L"].clone());
+
+        for _ in 0..8 {
+            let game_move = state.possible_moves().next().unwrap();
+            let next = state.after_move(game_move.clone()).unwrap();
+            let inferred = state.infer_move(&next).unwrap();
+            assert_eq!(state.after_move(inferred).unwrap(), next);
+            state = next;
+        }
+
+        let skip = Move::Skip { color: state.current_color() };
+        let next = state.after_move(skip.clone()).unwrap();
+        assert_eq!(state.infer_move(&next), Some(skip));
+    }
+
     #[test]
     fn test_game_state() {
         let start_piece = "PENTO_Y";
@@ -402,4 +534,55 @@ mod tests {
             assert!(!possible_moves.is_empty());
         }
     }
+
+    #[test]
+    fn test_round_after_every_color_moved() {
+        let mut state = GameState::new(PIECE_SHAPES_BY_NAME["PENTO_Y"].clone());
+
+        for _ in 0..4 {
+            assert_eq!(state.round, 1);
+            let game_move = state.possible_moves().next().unwrap();
+            state.perform_move(game_move).unwrap();
+        }
+
+        assert_eq!(state.round, 2);
+    }
+
+    #[test]
+    fn test_transformed_piece_is_deployed() {
+        let mut state = GameState::new(PIECE_SHAPES_BY_NAME["PENTO_Y"].clone());
+        let piece = state.possible_moves().find_map(|m| match m {
+            Move::Set { piece } if piece.shape() != piece.kind => Some(piece),
+            _ => None
+        }).expect("No transformed start piece available");
+
+        state.perform_move(Move::Set { piece: piece.clone() }).unwrap();
+        assert!(state.undeployed_shapes_of_color(piece.color).all(|s| *s != piece.kind));
+    }
+
+    #[test]
+    fn test_colors_without_moves_are_removed() {
+        let mut state = GameState::new(PIECE_SHAPES_BY_NAME["PENTO_Y"].clone());
+        for _ in 0..4 {
+            let game_move = state.possible_moves().next().unwrap();
+            state.perform_move(game_move).unwrap();
+        }
+
+        // Yellow is skipped once it has no pieces left to place
+        state.yellow_shapes.clear();
+        state.perform_move(Move::Skip { color: Color::Blue }).unwrap();
+        assert_eq!(state.ordered_colors, vec![Color::Blue, Color::Red, Color::Green]);
+        assert_eq!(state.current_color(), Color::Red);
+        assert_eq!(state.round, 2);
+        assert!(!state.is_game_over());
+
+        // The game ends once no color can move anymore
+        state.blue_shapes.clear();
+        state.red_shapes.clear();
+        state.green_shapes.clear();
+        state.perform_move(Move::Skip { color: Color::Red }).unwrap();
+        assert!(state.ordered_colors.is_empty());
+        assert_eq!(state.current_color(), Color::None);
+        assert!(state.is_game_over());
+    }
 }
@@ -1,9 +1,10 @@
-use socha_client_base::xml_node::XmlNode;
+use socha_client_base::{util::SCResult, xml_node::{FromXmlNode, XmlNode}};
 
 use super::{Color, Piece};
 
 /// A move in the game.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Move {
     /// A move that skips a round.
     Skip { color: Color },
@@ -20,6 +21,17 @@ impl Move {
     }
 }
 
+impl FromXmlNode for Move {
+    fn from_node(node: &XmlNode) -> SCResult<Self> {
+        let class = node.attribute("class")?;
+        match class {
+            "sc.plugin2021.SetMove" => Ok(Self::Set { piece: Piece::from_node(node.child_by_name("piece")?)? }),
+            "sc.plugin2021.SkipMove" => Ok(Self::Skip { color: Color::from_node(node.child_by_name("color")?)? }),
+            _ => Err(node.unknown_class(class))
+        }
+    }
+}
+
 impl From<Move> for XmlNode {
     fn from(game_move: Move) -> Self {
         match game_move {
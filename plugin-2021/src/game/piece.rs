@@ -1,9 +1,10 @@
-use socha_client_base::{util::SCResult, xml_node::FromXmlNode, xml_node::XmlNode};
+use socha_client_base::xml_node::{FromXmlNode, IntoXmlNode};
 
 use super::{Color, Vec2, PieceShape, Rotation};
 
 /// A game piece with color, position and transformed form.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, FromXmlNode, IntoXmlNode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
     /// The piece's untransformed shape
     pub kind: PieceShape,
@@ -14,6 +15,7 @@ pub struct Piece {
     /// The piece's color
     pub color: Color,
     /// The top left corner of the piece's rectangular bounding box
+    #[xml(child)]
     pub position: Vec2
 }
 
@@ -29,30 +31,3 @@ impl Piece {
         self.shape().coordinates().map(move |c| c + position)
     }
 }
-
-impl FromXmlNode for Piece {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
-        Ok(Self {
-            color: node.attribute("color")?.parse()?,
-            kind: node.attribute("kind")?.parse()?,
-            rotation: node.attribute("rotation")?.parse()?,
-            is_flipped: node.attribute("isFlipped")?.parse()?,
-            position: Vec2::from_node(node.child_by_name("position")?)?
-        })
-    }
-}
-
-impl From<Piece> for XmlNode {
-    fn from(piece: Piece) -> Self {
-        XmlNode::new("piece")
-            .attribute("color", piece.color.to_string())
-            .attribute("kind", piece.kind.to_string())
-            .attribute("rotation", piece.rotation.to_string())
-            .attribute("isFlipped", piece.is_flipped.to_string())
-            .child(XmlNode::new("position")
-                .attribute("x", piece.position.x.to_string())
-                .attribute("y", piece.position.y.to_string())
-                .build())
-            .build()
-    }
-}
@@ -70,7 +70,7 @@ impl CoordinateSet {
 
     fn index_of(coordinates: Vec2) -> usize {
         assert!(coordinates.x >= 0 && coordinates.y >= 0, "Coordinates have to be positive!");
-        assert!(coordinates.y < MAX_SIDE_LENGTH && coordinates.y < MAX_SIDE_LENGTH, "Vec2 are out of bounds!");
+        assert!(coordinates.x < MAX_SIDE_LENGTH && coordinates.y < MAX_SIDE_LENGTH, "Vec2 are out of bounds!");
 
         let i = (coordinates.y * MAX_SIDE_LENGTH) + coordinates.x;
         i as usize
@@ -109,7 +109,7 @@ impl fmt::Display for CoordinateSet {
             for x in 0..MAX_SIDE_LENGTH {
                 write!(f, "{}", if self.contains(Vec2::new(x, y)) { '#' } else { '.' })?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
@@ -263,8 +263,40 @@ impl fmt::Display for PieceShape {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for PieceShape {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        serializer.serialize_str(self.name)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PieceShape {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+        let name = <&str>::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl FromXmlNode for PieceShape {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
-        node.content().parse()
+        node.parse_content()
+    }
+}
+
+impl From<PieceShape> for XmlNode {
+    fn from(shape: PieceShape) -> Self {
+        XmlNode::new("shape").content(shape.name()).build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoordinateSet, MAX_SIDE_LENGTH, Vec2};
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_coordinates_out_of_bounds() {
+        CoordinateSet::new().insert(Vec2::new(MAX_SIDE_LENGTH, 0));
     }
 }
@@ -1,9 +1,10 @@
-use socha_client_base::{util::SCResult, xml_node::{FromXmlNode, XmlNode}};
+use socha_client_base::{util::SCResult, xml_node::{FromXmlNode, XmlNode, XmlNodeBuilder}};
 
 use super::Team;
 
 /// Metadata about a player.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Player {
     pub team: Team,
     pub display_name: String
@@ -17,3 +18,11 @@ impl FromXmlNode for Player {
         })
     }
 }
+
+impl<'a> From<Player> for XmlNodeBuilder<'a> {
+    fn from(player: Player) -> Self {
+        XmlNode::new("player")
+            .attribute("displayName", player.display_name)
+            .child(player.team)
+    }
+}
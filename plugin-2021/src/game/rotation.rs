@@ -6,6 +6,7 @@ pub const ROTATIONS: [Rotation; 4] = [Rotation::None, Rotation::Left, Rotation::
 
 /// Describes how a piece shape is rotated.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Rotation {
     None,
     Right,
@@ -3,8 +3,10 @@ use std::{fmt, str::FromStr};
 use socha_client_base::{error::SCError, util::HasOpponent, util::SCResult, xml_node::FromXmlNode, xml_node::XmlNode};
 
 /// A player's team.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Team {
+    #[default]
     None,
     One,
     Two
@@ -25,12 +27,6 @@ impl Team {
     }
 }
 
-impl Default for Team {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 impl HasOpponent for Team {
     /// Fetches the team's opponent team.
     fn opponent(self) -> Self {
@@ -67,6 +63,15 @@ impl fmt::Display for Team {
 
 impl FromXmlNode for Team {
     fn from_node(node: &XmlNode) -> SCResult<Self> {
-        node.content().parse()
+        node.parse_content()
+    }
+}
+
+impl From<Team> for XmlNode {
+    fn from(team: Team) -> Self {
+        XmlNode::new("color")
+            .attribute("class", "team")
+            .content(team.to_string().as_str())
+            .build()
     }
 }
@@ -1,11 +1,13 @@
 use std::{fmt, ops::{Add, Neg, Sub}};
 
-use socha_client_base::{util::SCResult, xml_node::{FromXmlNode, XmlNode}};
+use socha_client_base::xml_node::{FromXmlNode, IntoXmlNode};
 
 /// A vector in 2D-space. The x-axis
 /// usually points to the right while
 /// the y-axis points downwards.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, FromXmlNode, IntoXmlNode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[xml(name = "position")]
 pub struct Vec2 {
     pub x: i32,
     pub y: i32
@@ -116,12 +118,3 @@ impl Sub for Vec2 {
         Self::new(self.x - other.x, self.y - other.y)
     }
 }
-
-impl FromXmlNode for Vec2 {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
-        Ok(Self {
-            x: node.attribute("x")?.parse()?,
-            y: node.attribute("y")?.parse()?
-        })
-    }
-}
@@ -1,4 +1,4 @@
-use socha_client_base::plugin::{SCPlugin, HasPlayerColor, HasTurn};
+use socha_client_base::{plugin::{SCPlugin, HasPlayerColor, HasRules, HasTurn}, util::SCResult};
 use crate::game;
 
 #[derive(Debug)]
@@ -11,6 +11,14 @@ impl SCPlugin for SCPlugin2021 {
     type Move = game::Move;
 
     fn protocol_game_type<'a>() -> &'a str { "swc_2021_blokus" }
+
+    fn player_colors() -> Vec<game::Team> { vec![game::Team::One, game::Team::Two] }
+}
+
+impl HasPlayerColor for game::Player {
+    type PlayerColor = game::Team;
+
+    fn player_color(&self) -> Self::PlayerColor { self.team }
 }
 
 impl HasPlayerColor for game::GameState {
@@ -22,3 +30,19 @@ impl HasPlayerColor for game::GameState {
 impl HasTurn for game::GameState {
     fn turn(&self) -> u32 { self.turn }
 }
+
+impl HasRules for game::GameState {
+    type Move = game::Move;
+
+    fn possible_moves(&self) -> Vec<game::Move> { game::GameState::possible_moves(self).collect() }
+
+    fn validate_move(&self, game_move: &game::Move) -> SCResult<()> { game::GameState::validate_move(self, game_move) }
+
+    fn perform_move(&mut self, game_move: game::Move) -> SCResult<()> { game::GameState::perform_move(self, game_move) }
+
+    fn infer_move(&self, next: &Self) -> Option<game::Move> { game::GameState::infer_move(self, next) }
+
+    fn is_game_over(&self) -> bool { game::GameState::is_game_over(self) }
+
+    fn points(&self, color: game::Team) -> i32 { game::GameState::points(self, color) }
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "serde")]
+
+use socha_client_base::xml_serde;
+use socha_plugin_2021::game::{GameState, Move, PIECE_SHAPES_BY_NAME};
+
+fn played_state() -> GameState {
+    let mut state = GameState::new(PIECE_SHAPES_BY_NAME["PENTO_Y"].clone());
+    for _ in 0..2 {
+        let game_move = state.possible_moves().next().expect("No move available");
+        state.perform_move(game_move).expect("Could not perform move");
+    }
+    state
+}
+
+#[test]
+fn test_game_state_json_round_trip() {
+    let state = played_state();
+    let json = serde_json::to_string(&state).unwrap();
+    assert_eq!(serde_json::from_str::<GameState>(&json).unwrap(), state);
+}
+
+#[test]
+fn test_game_state_xml_round_trip() {
+    let state = played_state();
+    let node = xml_serde::to_node("state", &state).unwrap();
+    assert_eq!(node.name(), "state");
+    assert_eq!(node.attribute("turn").unwrap(), "2");
+    assert_eq!(xml_serde::from_node::<GameState>(&node).unwrap(), state);
+
+    let fresh = GameState::new(PIECE_SHAPES_BY_NAME["PENTO_Y"].clone());
+    let node = xml_serde::to_node("state", &fresh).unwrap();
+    assert_eq!(xml_serde::from_node::<GameState>(&node).unwrap(), fresh);
+}
+
+#[test]
+fn test_move_xml_round_trip() {
+    let state = played_state();
+    for game_move in state.possible_moves().take(3).chain(std::iter::once(Move::Skip { color: state.current_color() })) {
+        let node = xml_serde::to_node("move", &game_move).unwrap();
+        assert_eq!(xml_serde::from_node::<Move>(&node).unwrap(), game_move);
+    }
+}
@@ -0,0 +1,115 @@
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use log::debug;
+use xml::reader::{EventReader, XmlEvent as XmlReadEvent};
+use xml::writer::{EventWriter, EmitterConfig};
+use socha_client_base::util::SCResult;
+use socha_client_base::xml_node::XmlNode;
+
+/// A client's connection to the server, over
+/// which XML messages are exchanged.
+pub struct Connection {
+    stream: TcpStream,
+    reader: EventReader<BufReader<TcpStream>>,
+    sender: ConnectionSender
+}
+
+/// A handle through which messages can be sent
+/// to a client from other threads, e.g. while
+/// the client observes a room.
+#[derive(Clone)]
+pub struct ConnectionSender {
+    writer: Arc<Mutex<EventWriter<BufWriter<TcpStream>>>>
+}
+
+impl Connection {
+    /// Performs the opening handshake by waiting for
+    /// the client's <protocol> and answering it.
+    pub fn open(stream: TcpStream) -> SCResult<Self> {
+        // Messages are small and answered by the client,
+        // so waiting to coalesce them only adds latency
+        stream.set_nodelay(true)?;
+        let mut reader = EventReader::new(BufReader::new(stream.try_clone()?));
+
+        let mut emitter_config = EmitterConfig::new();
+        emitter_config.write_document_declaration = false;
+
+        let mut writer = emitter_config.create_writer(BufWriter::new(stream.try_clone()?));
+
+        loop {
+            match reader.next()? {
+                XmlReadEvent::StartElement { name, .. } if name.local_name == "protocol" => break,
+                XmlReadEvent::EndDocument => return Err("Connection ended before <protocol> was sent".into()),
+                _ => ()
+            }
+        }
+
+        writer.inner_mut().write_all("<protocol>".as_bytes())?;
+        writer.inner_mut().flush()?;
+
+        Ok(Self { stream, reader, sender: ConnectionSender { writer: Arc::new(Mutex::new(writer)) } })
+    }
+
+    /// Sends a message to the client.
+    pub fn send(&mut self, node: &XmlNode) -> SCResult<()> {
+        self.sender.send(node)
+    }
+
+    /// Fetches a handle for sending messages
+    /// to the client from other threads.
+    pub fn sender(&self) -> ConnectionSender {
+        self.sender.clone()
+    }
+
+    /// Blocks until the next message from the client arrives.
+    pub fn receive(&mut self) -> SCResult<XmlNode> {
+        let node = XmlNode::read_from(&mut self.reader)?;
+        debug!("Received {}", node);
+        Ok(node)
+    }
+
+    /// Checks whether the client is still connected
+    /// without consuming any of its messages.
+    pub fn is_alive(&self) -> bool {
+        if self.stream.set_nonblocking(true).is_err() {
+            return false;
+        }
+        let alive = match self.stream.peek(&mut [0]) {
+            Ok(read) => read > 0,
+            Err(e) => e.kind() == ErrorKind::WouldBlock
+        };
+        alive && self.stream.set_nonblocking(false).is_ok()
+    }
+
+    /// Sets the time after which `receive` fails.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> SCResult<()> {
+        self.stream.set_read_timeout(timeout)?;
+        Ok(())
+    }
+
+    /// Asks the client to close the connection and
+    /// terminates the XML stream.
+    pub fn close(mut self) -> SCResult<()> {
+        self.send(&XmlNode::new("sc.protocol.responses.CloseConnection").build())?;
+        {
+            let mut writer = self.sender.writer.lock().unwrap();
+            writer.inner_mut().write_all("</protocol>".as_bytes())?;
+            writer.inner_mut().flush()?;
+        }
+        self.stream.shutdown(Shutdown::Both)?;
+        Ok(())
+    }
+}
+
+impl ConnectionSender {
+    /// Sends a message to the client.
+    pub fn send(&self, node: &XmlNode) -> SCResult<()> {
+        debug!("Sending {}", node);
+        let mut writer = self.writer.lock().unwrap();
+        node.write_to(&mut *writer)?;
+        writer.inner_mut().flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,6 @@
+pub mod connection;
+pub mod lobby;
+pub mod plugin;
+pub mod room;
+pub mod server;
+pub mod util;
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+use log::info;
+use socha_client_base::protocol::{Joined, Prepared};
+use socha_client_base::util::SCResult;
+use socha_client_base::xml_node::{FromXmlNode, XmlNode};
+use crate::connection::Connection;
+use crate::room::RoomControl;
+use crate::util::random_id;
+
+/// The request with which a client
+/// asks to enter a room.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JoinRequest {
+    /// Joins any room of the given game type.
+    Join { game_type: String },
+    /// Joins the prepared room holding the reservation.
+    JoinPrepared { reservation_code: String }
+}
+
+/// A room whose players are known in advance
+/// and identified by their reservation codes.
+struct PreparedRoom {
+    room_id: String,
+    reservations: Vec<String>,
+    players: Vec<Option<Connection>>
+}
+
+/// A room that has all players it needs.
+pub struct FullRoom {
+    pub room_id: String,
+    /// The players in the order in which
+    /// they are assigned their colors.
+    pub players: Vec<Connection>,
+    pub control: Arc<RoomControl>
+}
+
+/// Pairs joining clients into rooms.
+#[derive(Default)]
+pub struct Lobby {
+    waiting: Option<(String, Connection)>,
+    prepared: HashMap<String, PreparedRoom>,
+    controls: HashMap<String, Arc<RoomControl>>
+}
+
+impl FromXmlNode for JoinRequest {
+    fn from_node(node: &XmlNode) -> SCResult<Self> {
+        match node.name() {
+            "join" => Ok(Self::Join { game_type: node.attribute("gameType")?.to_owned() }),
+            "joinPrepared" => Ok(Self::JoinPrepared { reservation_code: node.attribute("reservationCode")?.to_owned() }),
+            name => Err(format!("Expected a join request, but got <{}>", name).into())
+        }
+    }
+}
+
+impl Lobby {
+    /// Creates a new, empty lobby.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prepares a room for the given number of players
+    /// and returns their reservation codes.
+    pub fn prepare(&mut self, player_count: usize, paused: bool) -> Prepared {
+        let room_id = random_id();
+        let reservations: Vec<_> = (0..player_count).map(|_| random_id()).collect();
+        info!("Prepared room {} with reservations {:?}", room_id, reservations);
+
+        self.controls.insert(room_id.clone(), Arc::new(RoomControl::new(paused)));
+        self.prepared.insert(room_id.clone(), PreparedRoom {
+            room_id: room_id.clone(),
+            reservations: reservations.clone(),
+            players: (0..player_count).map(|_| None).collect()
+        });
+        Prepared { room_id, reservations }
+    }
+
+    /// Fetches the control of an open room.
+    pub fn control(&self, room_id: &str) -> Option<Arc<RoomControl>> {
+        self.controls.get(room_id).cloned()
+    }
+
+    /// Forgets a room after its game has ended.
+    pub fn remove(&mut self, room_id: &str) {
+        self.controls.remove(room_id);
+    }
+
+    /// Adds a client to a room of the given game type.
+    /// Returns the room once it is full and the game
+    /// can begin.
+    pub fn join(&mut self, request: JoinRequest, game_type: &str, mut connection: Connection) -> SCResult<Option<FullRoom>> {
+        match request {
+            JoinRequest::Join { .. } => {
+                // A waiting client that disconnected in the meantime
+                // cannot play, so the new client waits in its place
+                if let Some((room_id, _)) = self.waiting.as_ref().filter(|(_, w)| !w.is_alive()) {
+                    info!("Dropping disconnected player waiting in room {}", room_id);
+                    self.controls.remove(room_id);
+                    self.waiting = None;
+                }
+
+                if let Some((room_id, waiting)) = self.waiting.take() {
+                    connection.send(&Joined { room_id: room_id.clone(), game_type: game_type.to_owned() }.into())?;
+                    info!("Second player joined room {}", room_id);
+                    let control = self.controls.entry(room_id.clone()).or_default().clone();
+                    Ok(Some(FullRoom { room_id, players: vec![waiting, connection], control }))
+                } else {
+                    let room_id = random_id();
+                    connection.send(&Joined { room_id: room_id.clone(), game_type: game_type.to_owned() }.into())?;
+                    info!("First player joined room {}", room_id);
+                    self.controls.insert(room_id.clone(), Arc::default());
+                    self.waiting = Some((room_id, connection));
+                    Ok(None)
+                }
+            },
+            JoinRequest::JoinPrepared { reservation_code } => {
+                let room = self.prepared.values_mut()
+                    .find(|r| r.reservations.contains(&reservation_code))
+                    .ok_or_else(|| format!("Unknown reservation code {}", reservation_code))?;
+                let index = room.reservations.iter().position(|r| r == &reservation_code).unwrap();
+
+                if room.players[index].is_some() {
+                    return Err(format!("Reservation {} has already been used", reservation_code).into());
+                }
+
+                connection.send(&Joined { room_id: room.room_id.clone(), game_type: game_type.to_owned() }.into())?;
+                info!("Player joined prepared room {} with reservation {}", room.room_id, reservation_code);
+                room.players[index] = Some(connection);
+
+                if room.players.iter().all(|p| p.is_some()) {
+                    let room_id = room.room_id.clone();
+                    let mut room = self.prepared.remove(&room_id).unwrap();
+                    let players = mem::take(&mut room.players).into_iter().flatten().collect();
+                    let control = self.controls.entry(room_id.clone()).or_default().clone();
+                    Ok(Some(FullRoom { room_id, players, control }))
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::env;
+use std::process;
+use log::error;
+use socha_client_base::util::SCResult;
+use socha_plugin_2020::plugin::SCPlugin2020;
+use socha_plugin_2021::plugin::SCPlugin2021;
+use socha_server::plugin::SCServerPlugin;
+use socha_server::room::Timeouts;
+use socha_server::server::SCServer;
+
+const USAGE: &str = "Usage: socha-server [--host <host>] [--port <port>] [--game <game type>] [--password <password>]";
+
+fn run(host: &str, port: u16, game_type: &str, password: Option<&str>) -> SCResult<()> {
+    let address = (host, port);
+    match game_type {
+        "swc_2020_hive" => with_password(SCServer::<SCPlugin2020>::bind(address, Timeouts::default())?, password).run(),
+        "swc_2021_blokus" => with_password(SCServer::<SCPlugin2021>::bind(address, Timeouts::default())?, password).run(),
+        _ => Err(format!("Unsupported game type {}", game_type).into())
+    }
+}
+
+fn with_password<P>(server: SCServer<P>, password: Option<&str>) -> SCServer<P> where P: SCServerPlugin + 'static {
+    match password {
+        Some(password) => server.with_password(password),
+        None => server
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    let mut host = "localhost".to_owned();
+    let mut port = 13050;
+    let mut game_type = "swc_2021_blokus".to_owned();
+    let mut password = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        match (arg.as_str(), value) {
+            ("--host", Some(value)) => host = value,
+            ("--port", Some(value)) => port = value.parse().unwrap_or_else(|_| {
+                eprintln!("Invalid port {}", value);
+                process::exit(1);
+            }),
+            ("--game", Some(value)) => game_type = value,
+            ("--password", Some(value)) => password = Some(value),
+            _ => {
+                eprintln!("{}", USAGE);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Err(e) = run(&host, port, &game_type, password.as_deref()) {
+        error!("Server failed: {:?}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use socha_client_base::plugin::SCPlugin;
+use socha_plugin_2020::game::{self as game_2020, BOARD_RADIUS};
+use socha_plugin_2020::plugin::SCPlugin2020;
+use socha_plugin_2021::game::{self as game_2021, PIECE_SHAPES};
+use socha_plugin_2021::plugin::SCPlugin2021;
+use crate::util::random_u64;
+
+/// The number of obstructed fields on a new Hive board.
+const OBSTRUCTED_FIELD_COUNT: usize = 3;
+
+/// Extends a plugin with the setup logic required
+/// to host games of it. The rules themselves are
+/// provided by the game state's `HasRules` implementation.
+pub trait SCServerPlugin: SCPlugin {
+    /// Creates the state in which a new game begins.
+    fn initial_state() -> Self::GameState;
+
+    /// Fetches the player with the given color.
+    fn player(state: &Self::GameState, color: Self::PlayerColor) -> Self::Player;
+}
+
+impl SCServerPlugin for SCPlugin2020 {
+    fn initial_state() -> game_2020::GameState {
+        let empty = game_2020::Board::filling_radius(BOARD_RADIUS, HashMap::new());
+        let mut coords: Vec<_> = empty.fields().map(|(c, _)| c).collect();
+        let obstructed: HashMap<_, _> = (0..OBSTRUCTED_FIELD_COUNT)
+            .map(|_| coords.swap_remove((random_u64() % coords.len() as u64) as usize))
+            .map(|c| (c, game_2020::Field::new(Vec::new(), true)))
+            .collect();
+        game_2020::GameState::new(game_2020::Board::filling_radius(BOARD_RADIUS, obstructed))
+    }
+
+    fn player(state: &game_2020::GameState, color: game_2020::PlayerColor) -> game_2020::Player { state.player(color).clone() }
+}
+
+impl SCServerPlugin for SCPlugin2021 {
+    fn initial_state() -> game_2021::GameState {
+        // The X-pentomino cannot cover a corner and is therefore no valid start piece
+        let pentominos: Vec<_> = PIECE_SHAPES.iter().filter(|s| s.name().starts_with("PENTO") && s.name() != "PENTO_X").collect();
+        let start_piece = pentominos[(random_u64() % pentominos.len() as u64) as usize];
+        game_2021::GameState::new(start_piece.clone())
+    }
+
+    fn player(state: &game_2021::GameState, color: game_2021::Team) -> game_2021::Player {
+        if color == state.second.team { state.second.clone() } else { state.first.clone() }
+    }
+}
@@ -0,0 +1,257 @@
+use std::convert::TryFrom;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use socha_client_base::plugin::{HasPlayerColor, HasRules};
+use socha_client_base::protocol::{Data, GameResult, Left, PlayerScore, Room, ScoreAggregation, ScoreCause, ScoreDefinition, ScoreFragment};
+use socha_client_base::util::SCResult;
+use socha_client_base::xml_node::{FromXmlNode, XmlNode};
+use crate::connection::{Connection, ConnectionSender};
+use crate::plugin::SCServerPlugin;
+
+/// The time limits for a single move.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Timeouts {
+    /// The time after which a move is considered
+    /// too late, losing the game with `SOFT_TIMEOUT`.
+    pub soft: Option<Duration>,
+    /// The time after which the server stops waiting
+    /// for a move, losing the game with `HARD_TIMEOUT`.
+    pub hard: Option<Duration>
+}
+
+/// The administrative state of a room, which is shared
+/// between the running game and administrators.
+#[derive(Default)]
+pub struct RoomControl {
+    state: Mutex<ControlState>,
+    changed: Condvar
+}
+
+#[derive(Default)]
+struct ControlState {
+    paused: bool,
+    pending_steps: usize,
+    observers: Vec<ConnectionSender>
+}
+
+/// A player's failure that ended the game early.
+struct Violation {
+    player: usize,
+    cause: ScoreCause,
+    reason: String
+}
+
+/// A running game between connected players.
+pub struct GameRoom<P> where P: SCServerPlugin {
+    room_id: String,
+    players: Vec<Connection>,
+    colors: Vec<P::PlayerColor>,
+    timeouts: Timeouts,
+    control: Arc<RoomControl>,
+    state: P::GameState
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self { soft: Some(Duration::from_secs(2)), hard: Some(Duration::from_secs(10)) }
+    }
+}
+
+impl RoomControl {
+    /// Creates a new control for a room
+    /// that may begin paused.
+    pub fn new(paused: bool) -> Self {
+        Self { state: Mutex::new(ControlState { paused, ..Default::default() }), changed: Condvar::new() }
+    }
+
+    /// Pauses or resumes the game.
+    pub fn set_paused(&self, paused: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.paused = paused;
+        state.pending_steps = 0;
+        self.changed.notify_all();
+    }
+
+    /// Lets a paused game advance by a single move.
+    pub fn step(&self) {
+        self.state.lock().unwrap().pending_steps += 1;
+        self.changed.notify_all();
+    }
+
+    /// Forwards the room's future messages to the given client.
+    pub fn add_observer(&self, observer: ConnectionSender) {
+        self.state.lock().unwrap().observers.push(observer);
+    }
+
+    /// Blocks while the game is paused and no step is pending.
+    fn wait_until_resumed(&self) {
+        let mut state = self.state.lock().unwrap();
+        while state.paused && state.pending_steps == 0 {
+            state = self.changed.wait(state).unwrap();
+        }
+        state.pending_steps = state.pending_steps.saturating_sub(1);
+    }
+
+    /// Sends a message to every observer, dropping
+    /// those that cannot be reached anymore.
+    fn notify_observers(&self, node: &XmlNode) {
+        self.state.lock().unwrap().observers.retain(|observer| match observer.send(node) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Removing unreachable observer: {:?}", e);
+                false
+            }
+        });
+    }
+}
+
+impl<P> GameRoom<P> where P: SCServerPlugin {
+    /// Creates a new room with the players ordered
+    /// as in `SCPlugin::player_colors`.
+    pub fn new(room_id: String, players: Vec<Connection>, timeouts: Timeouts, control: Arc<RoomControl>) -> Self {
+        Self {
+            room_id,
+            players,
+            colors: P::player_colors(),
+            timeouts,
+            control,
+            state: P::initial_state()
+        }
+    }
+
+    /// Plays the game until it ends and closes
+    /// the connections afterwards.
+    pub fn run(mut self) -> SCResult<GameResult<P>> {
+        info!("Starting game in room {}", self.room_id);
+
+        for i in 0..self.players.len() {
+            let color = self.colors[i];
+            if let Err(e) = self.send(i, Data::WelcomeMessage { color }) {
+                warn!("Could not welcome player {} in room {}: {:?}", i, self.room_id, e);
+            }
+        }
+
+        let violation = loop {
+            self.broadcast(Data::Memento { state: self.state.clone() })?;
+
+            if self.state.is_game_over() {
+                break None;
+            }
+
+            self.control.wait_until_resumed();
+            let color = self.state.player_color();
+            let player = self.colors.iter().position(|&c| c == color).ok_or_else(|| format!("No player has color {:?}", color))?;
+            if let Err(e) = self.send(player, Data::MoveRequest) {
+                break Some(Violation { player, cause: ScoreCause::Left, reason: format!("The move could not be requested: {:?}", e) });
+            }
+
+            match self.receive_move(player) {
+                Ok(game_move) => if let Err(e) = self.state.validate_move(&game_move) {
+                    break Some(Violation { player, cause: ScoreCause::RuleViolation, reason: format!("Invalid move {:?}: {:?}", game_move, e) });
+                } else {
+                    self.state.perform_move(game_move)?;
+                },
+                Err(violation) => break Some(violation)
+            }
+        };
+
+        let result = self.result(violation);
+        info!("Game in room {} ended: {:?}", self.room_id, result);
+        self.broadcast(Data::GameResult(result.clone()))?;
+
+        let left = Left { room_id: self.room_id.clone() }.into();
+        for (i, mut player) in self.players.into_iter().enumerate() {
+            if let Err(e) = player.send(&left).and_then(|()| player.close()) {
+                warn!("Could not close the connection to player {} in room {}: {:?}", i, self.room_id, e);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Sends room data to a single player.
+    fn send(&mut self, player: usize, data: Data<P>) -> SCResult<()> {
+        let node = XmlNode::try_from(Room::<P> { room_id: self.room_id.clone(), data })?;
+        self.players[player].send(&node)
+    }
+
+    /// Sends room data to every player and observer,
+    /// skipping players that cannot be reached.
+    fn broadcast(&mut self, data: Data<P>) -> SCResult<()> {
+        let node = XmlNode::try_from(Room::<P> { room_id: self.room_id.clone(), data })?;
+        for (i, player) in self.players.iter_mut().enumerate() {
+            if let Err(e) = player.send(&node) {
+                warn!("Could not send to player {} in room {}: {:?}", i, self.room_id, e);
+            }
+        }
+        self.control.notify_observers(&node);
+        Ok(())
+    }
+
+    /// Waits for the given player's move.
+    fn receive_move(&mut self, player: usize) -> Result<P::Move, Violation> {
+        let start = Instant::now();
+        let timeouts = self.timeouts;
+        let connection = &mut self.players[player];
+        let violation = |cause, reason: String| Violation { player, cause, reason };
+
+        connection.set_read_timeout(timeouts.hard).map_err(|e| violation(ScoreCause::Left, format!("{:?}", e)))?;
+
+        loop {
+            let node = connection.receive().map_err(|e| if timeouts.hard.filter(|&t| start.elapsed() >= t).is_some() {
+                violation(ScoreCause::HardTimeout, "The move was not sent in time".to_owned())
+            } else {
+                violation(ScoreCause::Left, format!("The connection was lost: {:?}", e))
+            })?;
+
+            match node.name() {
+                "room" => match <Room<P>>::from_node(&node) {
+                    Ok(Room { data: Data::Move(game_move), .. }) => {
+                        return if timeouts.soft.filter(|&t| start.elapsed() > t).is_some() {
+                            Err(violation(ScoreCause::SoftTimeout, format!("The move took {} ms", start.elapsed().as_millis())))
+                        } else {
+                            Ok(game_move)
+                        };
+                    },
+                    Ok(room) => warn!("Ignoring room data {:?} while waiting for a move", room.data),
+                    Err(e) => return Err(violation(ScoreCause::RuleViolation, format!("Could not parse move: {:?}", e)))
+                },
+                "close" | "sc.protocol.responses.CloseConnection" => return Err(violation(ScoreCause::Left, "The player left the game".to_owned())),
+                name => warn!("Ignoring unexpected message <{}> while waiting for a move", name)
+            }
+        }
+    }
+
+    /// Determines the game's result.
+    fn result(&self, violation: Option<Violation>) -> GameResult<P> {
+        let points: Vec<_> = self.colors.iter().map(|&c| self.state.points(c)).collect();
+        let winners: Vec<_> = match violation {
+            Some(ref v) => (0..self.players.len()).filter(|&i| i != v.player).collect(),
+            None => {
+                let max_points = points.iter().max().cloned().unwrap_or(0);
+                let leaders: Vec<_> = (0..self.players.len()).filter(|&i| points[i] == max_points).collect();
+                if leaders.len() == 1 { leaders } else { Vec::new() }
+            }
+        };
+
+        GameResult {
+            definition: ScoreDefinition {
+                fragments: vec![
+                    ScoreFragment { name: "Siegpunkte".to_owned(), aggregation: ScoreAggregation::Sum, relevant_for_ranking: true },
+                    ScoreFragment { name: "∅ Punkte".to_owned(), aggregation: ScoreAggregation::Average, relevant_for_ranking: true }
+                ]
+            },
+            scores: (0..self.players.len()).map(|i| {
+                // Winners get two, players in a draw one point
+                let win_points = if winners.contains(&i) { 2.0 } else if winners.is_empty() { 1.0 } else { 0.0 };
+                let parts = vec![win_points, f64::from(points[i])];
+                match violation {
+                    Some(ref v) if v.player == i => PlayerScore { cause: v.cause.clone(), reason: v.reason.clone(), parts },
+                    _ => PlayerScore { cause: ScoreCause::Regular, reason: String::new(), parts }
+                }
+            }).collect(),
+            winners: winners.iter().map(|&i| P::player(&self.state, self.colors[i])).collect()
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::marker::PhantomData;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use log::{info, error};
+use socha_client_base::protocol::{Authenticate, Observe, Observed, Pause, Prepare, Step};
+use socha_client_base::util::SCResult;
+use socha_client_base::xml_node::{FromXmlNode, XmlNode};
+use crate::connection::Connection;
+use crate::lobby::{JoinRequest, Lobby};
+use crate::plugin::SCServerPlugin;
+use crate::room::{GameRoom, Timeouts};
+
+/// A local stand-in for the game server which
+/// pairs joining clients into rooms and hosts
+/// games between them.
+pub struct SCServer<P> where P: SCServerPlugin {
+    listener: TcpListener,
+    timeouts: Timeouts,
+    password: Option<String>,
+    lobby: Arc<Mutex<Lobby>>,
+    plugin: PhantomData<fn() -> P>
+}
+
+impl<P> SCServer<P> where P: SCServerPlugin + 'static {
+    /// Creates a new server listening on the given address.
+    pub fn bind(address: impl ToSocketAddrs, timeouts: Timeouts) -> SCResult<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+            timeouts,
+            password: None,
+            lobby: Arc::new(Mutex::new(Lobby::new())),
+            plugin: PhantomData
+        })
+    }
+
+    /// Sets the password with which administrators
+    /// authenticate. Without one, administrative
+    /// connections are rejected.
+    pub fn with_password(mut self, password: &str) -> Self {
+        self.password = Some(password.to_owned());
+        self
+    }
+
+    /// Fetches the address the server is listening on.
+    pub fn local_addr(&self) -> SCResult<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Prepares a room and returns the reservation codes
+    /// with which the players can join it.
+    pub fn prepare_game(&self) -> Vec<String> {
+        self.lobby.lock().unwrap().prepare(P::player_colors().len(), false).reservations
+    }
+
+    /// Blocks the thread and accepts clients, hosting
+    /// each game on a separate thread.
+    pub fn run(&self) -> SCResult<()> {
+        info!("Listening on {}", self.local_addr()?);
+
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let lobby = self.lobby.clone();
+            let timeouts = self.timeouts;
+            let password = self.password.clone();
+
+            thread::spawn(move || {
+                if let Err(e) = Self::handle_client(stream, lobby, timeouts, password) {
+                    error!("Error while handling client: {:?}", e);
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Lets the client join a room and plays the game
+    /// if the room is full.
+    fn handle_client(stream: TcpStream, lobby: Arc<Mutex<Lobby>>, timeouts: Timeouts, password: Option<String>) -> SCResult<()> {
+        info!("Accepted client {}", stream.peer_addr()?);
+        let mut connection = Connection::open(stream)?;
+        let node = connection.receive()?;
+
+        if node.name() == "authenticate" {
+            return Self::handle_administrator(connection, Authenticate::from_node(&node)?, lobby, password);
+        }
+
+        let request = JoinRequest::from_node(&node)?;
+
+        if let JoinRequest::Join { ref game_type } = request {
+            if game_type != P::protocol_game_type() {
+                connection.close()?;
+                return Err(format!("Unsupported game type {}, this server hosts {}", game_type, P::protocol_game_type()).into());
+            }
+        }
+
+        let room = lobby.lock().unwrap().join(request, P::protocol_game_type(), connection)?;
+
+        if let Some(room) = room {
+            let room_id = room.room_id.clone();
+            let result = GameRoom::<P>::new(room.room_id, room.players, timeouts, room.control).run();
+            lobby.lock().unwrap().remove(&room_id);
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Handles administrative requests until
+    /// the administrator disconnects.
+    fn handle_administrator(mut connection: Connection, authenticate: Authenticate, lobby: Arc<Mutex<Lobby>>, password: Option<String>) -> SCResult<()> {
+        if password.as_deref() != Some(authenticate.password.as_str()) {
+            connection.close()?;
+            return Err("Administrator could not be authenticated".into());
+        }
+        info!("Authenticated administrator");
+
+        loop {
+            let node = match connection.receive() {
+                Ok(node) => node,
+                Err(e) => {
+                    info!("Administrator disconnected: {:?}", e);
+                    return Ok(());
+                }
+            };
+
+            match Self::handle_admin_request(&node, &connection, &lobby) {
+                Ok(Some(response)) => connection.send(&response)?,
+                Ok(None) => (),
+                Err(e) => {
+                    error!("Could not handle administrative request <{}>: {:?}", node.name(), e);
+                    connection.send(&XmlNode::new("error").attribute("message", format!("{:?}", e)).build())?;
+                }
+            }
+        }
+    }
+
+    /// Handles a single administrative request,
+    /// returning the response if there is one.
+    fn handle_admin_request(node: &XmlNode, connection: &Connection, lobby: &Mutex<Lobby>) -> SCResult<Option<XmlNode>> {
+        let control = |room_id: &str| lobby.lock().unwrap().control(room_id).ok_or_else(|| format!("Unknown room {}", room_id));
+
+        match node.name() {
+            "prepare" => {
+                let prepare = Prepare::from_node(node)?;
+                if prepare.game_type != P::protocol_game_type() {
+                    return Err(format!("Unsupported game type {}, this server hosts {}", prepare.game_type, P::protocol_game_type()).into());
+                }
+                if prepare.slots.len() != P::player_colors().len() {
+                    return Err(format!("Expected {} slots, but got {}", P::player_colors().len(), prepare.slots.len()).into());
+                }
+                Ok(Some(lobby.lock().unwrap().prepare(prepare.slots.len(), prepare.pause).into()))
+            },
+            "observe" => {
+                let observe = Observe::from_node(node)?;
+                control(&observe.room_id)?.add_observer(connection.sender());
+                info!("Administrator observes room {}", observe.room_id);
+                Ok(Some(Observed { room_id: observe.room_id }.into()))
+            },
+            "pause" => {
+                let pause = Pause::from_node(node)?;
+                control(&pause.room_id)?.set_paused(pause.pause);
+                Ok(None)
+            },
+            "step" => {
+                let step = Step::from_node(node)?;
+                control(&step.room_id)?.step();
+                Ok(None)
+            },
+            name => Err(format!("Unrecognized administrative request <{}>", name).into())
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Generates a pseudo-random number using the
+/// randomly seeded hasher from the standard library.
+/// This is by no means cryptographically secure, but
+/// sufficient for picking room ids or start pieces.
+pub fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Generates a random, hexadecimal identifier.
+pub fn random_id() -> String {
+    format!("{:016x}", random_u64())
+}
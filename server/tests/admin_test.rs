@@ -0,0 +1,164 @@
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use socha_client_base::admin_client::SCAdminClient;
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::observer::{SCObserver, SCObserverDelegate};
+use socha_client_base::plugin::HasTurn;
+use socha_client_base::protocol::{Data, GameResult, Prepare, Room, SlotDescriptor};
+use socha_client_base::xml_node::FromXmlNode;
+use socha_plugin_2021::game::{GameState, Move, Team};
+use socha_plugin_2021::plugin::SCPlugin2021;
+use socha_server::room::Timeouts;
+use socha_server::server::SCServer;
+
+const PASSWORD: &str = "examplepassword";
+
+/// A delegate that places the start piece
+/// and skips every move afterwards.
+struct SkippingDelegate {
+    results: Sender<GameResult<SCPlugin2021>>
+}
+
+impl SCClientDelegate for SkippingDelegate {
+    type Plugin = SCPlugin2021;
+
+    fn on_game_end(&mut self, result: GameResult<SCPlugin2021>) {
+        self.results.send(result).unwrap();
+    }
+
+    fn request_move(&mut self, state: &GameState, _my_team: Team, _history: &GameHistory<SCPlugin2021>, _budget: &MoveBudget<Move>) -> Move {
+        if state.is_first_move() {
+            state.possible_moves().next().expect("No start move available")
+        } else {
+            Move::Skip { color: state.current_color() }
+        }
+    }
+}
+
+/// A delegate that forwards everything it observes.
+struct ForwardingObserver {
+    states: Sender<GameState>,
+    results: Sender<GameResult<SCPlugin2021>>
+}
+
+impl SCObserverDelegate for ForwardingObserver {
+    type Plugin = SCPlugin2021;
+
+    fn on_update_state(&mut self, state: &GameState) {
+        self.states.send(state.clone()).unwrap();
+    }
+
+    fn on_game_end(&mut self, result: GameResult<SCPlugin2021>) {
+        self.results.send(result).unwrap();
+    }
+}
+
+fn start_server() -> u16 {
+    let server = SCServer::<SCPlugin2021>::bind("127.0.0.1:0", Timeouts::default()).unwrap().with_password(PASSWORD);
+    let port = server.local_addr().unwrap().port();
+    thread::spawn(move || server.run().unwrap());
+    port
+}
+
+fn prepare_request(pause: bool) -> Prepare {
+    Prepare {
+        game_type: "swc_2021_blokus".to_owned(),
+        pause,
+        slots: vec![SlotDescriptor::default(), SlotDescriptor::default()]
+    }
+}
+
+/// Blocks until the observed room sends data.
+fn receive_data(admin: &mut SCAdminClient) -> Data<SCPlugin2021> {
+    loop {
+        let node = admin.receive().unwrap();
+        if node.name() == "room" {
+            return <Room<SCPlugin2021>>::from_node(&node).unwrap().data;
+        }
+    }
+}
+
+#[test]
+fn test_prepare_observe_and_step() {
+    let port = start_server();
+    let mut admin = SCAdminClient::connect("127.0.0.1", port, PASSWORD).unwrap();
+    let prepared = admin.prepare(prepare_request(true)).unwrap();
+    assert_eq!(prepared.reservations.len(), 2);
+    assert_eq!(admin.observe(&prepared.room_id).unwrap().room_id, prepared.room_id);
+
+    let (sender, receiver) = channel();
+    let clients: Vec<_> = prepared.reservations.into_iter().map(|reservation| {
+        let client = SCClient::new(SkippingDelegate { results: sender.clone() }, DebugMode::default());
+        thread::spawn(move || client.run("127.0.0.1", port, Some(&reservation)).unwrap())
+    }).collect();
+
+    match receive_data(&mut admin) {
+        Data::Memento { state } => assert_eq!(state.turn(), 0),
+        data => panic!("Expected the initial state, got {:?}", data)
+    }
+
+    admin.step(&prepared.room_id).unwrap();
+    match receive_data(&mut admin) {
+        Data::Memento { state } => assert_eq!(state.turn(), 1),
+        data => panic!("Expected the state after one step, got {:?}", data)
+    }
+
+    admin.pause(&prepared.room_id, false).unwrap();
+    let result = loop {
+        if let Data::GameResult(result) = receive_data(&mut admin) {
+            break result;
+        }
+    };
+
+    for client in clients {
+        client.join().unwrap();
+    }
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![result.clone(), result]);
+}
+
+#[test]
+fn test_invalid_requests() {
+    let port = start_server();
+    let mut admin = SCAdminClient::connect("127.0.0.1", port, PASSWORD).unwrap();
+    assert!(admin.observe("unknown").is_err());
+    assert!(admin.prepare(Prepare { slots: Vec::new(), ..prepare_request(false) }).is_err());
+    assert!(admin.prepare(prepare_request(false)).is_ok());
+
+    let mut intruder = SCAdminClient::connect("127.0.0.1", port, "wrongpassword").unwrap();
+    assert!(intruder.prepare(prepare_request(false)).is_err());
+}
+
+#[test]
+fn test_observer() {
+    let port = start_server();
+    let mut admin = SCAdminClient::connect("127.0.0.1", port, PASSWORD).unwrap();
+    let prepared = admin.prepare(prepare_request(false)).unwrap();
+    admin.observe(&prepared.room_id).unwrap();
+
+    let (state_sender, state_receiver) = channel();
+    let (result_sender, result_receiver) = channel();
+    let room_id = prepared.room_id.clone();
+    let observer = thread::spawn(move || {
+        SCObserver::new(ForwardingObserver { states: state_sender, results: result_sender }).run_with(admin, &room_id).unwrap()
+    });
+
+    let (sender, receiver) = channel();
+    let clients: Vec<_> = prepared.reservations.into_iter().map(|reservation| {
+        let client = SCClient::new(SkippingDelegate { results: sender.clone() }, DebugMode::default());
+        thread::spawn(move || client.run("127.0.0.1", port, Some(&reservation)).unwrap())
+    }).collect();
+
+    for client in clients {
+        client.join().unwrap();
+    }
+    observer.join().unwrap();
+
+    let states: Vec<_> = state_receiver.try_iter().collect();
+    assert_eq!(states[0].turn(), 0);
+    assert!(states.windows(2).all(|w| w[1].turn() == w[0].turn() + 1));
+    let results: Vec<_> = result_receiver.try_iter().collect();
+    assert_eq!(results.len(), 1);
+    assert_eq!(receiver.try_iter().next(), Some(results[0].clone()));
+}
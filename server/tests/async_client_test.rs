@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use std::thread;
+use socha_client_base::async_client::{AsyncSCClient, AsyncSCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::protocol::{GameResult, ScoreCause};
+use socha_plugin_2021::game::{GameState, Move, Team};
+use socha_plugin_2021::plugin::SCPlugin2021;
+use socha_server::room::Timeouts;
+use socha_server::server::SCServer;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+/// An asynchronous delegate that places the
+/// start piece and skips every move afterwards.
+struct AsyncSkippingDelegate {
+    results: UnboundedSender<GameResult<SCPlugin2021>>
+}
+
+#[async_trait]
+impl AsyncSCClientDelegate for AsyncSkippingDelegate {
+    type Plugin = SCPlugin2021;
+
+    fn on_game_end(&mut self, result: GameResult<SCPlugin2021>) {
+        self.results.send(result).unwrap();
+    }
+
+    async fn request_move(&mut self, state: &GameState, _my_team: Team, _history: &GameHistory<SCPlugin2021>, _budget: &MoveBudget<Move>) -> Move {
+        if state.is_first_move() {
+            state.possible_moves().next().expect("No start move available")
+        } else {
+            Move::Skip { color: state.current_color() }
+        }
+    }
+}
+
+/// An asynchronous delegate that panics instead of placing
+/// the start piece and reports the lifecycle hooks it receives.
+struct AsyncPanickingDelegate {
+    events: UnboundedSender<&'static str>
+}
+
+#[async_trait]
+impl AsyncSCClientDelegate for AsyncPanickingDelegate {
+    type Plugin = SCPlugin2021;
+
+    fn on_joined(&mut self, _room_id: &str) {
+        self.events.send("joined").unwrap();
+    }
+
+    fn on_close(&mut self) {
+        self.events.send("close").unwrap();
+    }
+
+    async fn request_move(&mut self, state: &GameState, _my_team: Team, _history: &GameHistory<SCPlugin2021>, _budget: &MoveBudget<Move>) -> Move {
+        if state.is_first_move() {
+            panic!("Delegate failure");
+        }
+        Move::Skip { color: state.current_color() }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_concurrent_async_games() {
+    let server = SCServer::<SCPlugin2021>::bind("127.0.0.1:0", Timeouts::default()).unwrap();
+    let port = server.local_addr().unwrap().port();
+    thread::spawn(move || server.run().unwrap());
+
+    let (sender, mut receiver) = unbounded_channel();
+    let clients: Vec<_> = (0..4).map(|_| {
+        let client = AsyncSCClient::new(AsyncSkippingDelegate { results: sender.clone() });
+        tokio::spawn(client.run("127.0.0.1", port, None))
+    }).collect();
+    drop(sender);
+
+    for client in clients {
+        let summaries = client.await.unwrap().unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert!(summaries[0].my_score().is_some());
+        assert!(!summaries[0].turns.is_empty());
+        assert_eq!(summaries[0].history.moves().len(), summaries[0].turns.len());
+    }
+
+    let mut results = Vec::new();
+    while let Some(result) = receiver.recv().await {
+        results.push(result);
+    }
+    assert_eq!(results.len(), 4);
+    assert!(results.iter().flat_map(|r| &r.scores).all(|s| s.cause == ScoreCause::Regular));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_async_panicking_delegate() {
+    let server = SCServer::<SCPlugin2021>::bind("127.0.0.1:0", Timeouts::default()).unwrap();
+    let port = server.local_addr().unwrap().port();
+    thread::spawn(move || server.run().unwrap());
+
+    let (sender, _receiver) = unbounded_channel();
+    let (events, mut event_receiver) = unbounded_channel();
+    let skipping = tokio::spawn(AsyncSCClient::new(AsyncSkippingDelegate { results: sender }).run("127.0.0.1", port, None));
+    let panicking = tokio::spawn(AsyncSCClient::new(AsyncPanickingDelegate { events }).run("127.0.0.1", port, None));
+
+    skipping.await.unwrap().unwrap();
+    let summaries = panicking.await.unwrap().unwrap();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].my_score().map(|s| &s.cause), Some(&ScoreCause::Regular));
+    assert!(summaries[0].turns.iter().any(|t| t.fallback));
+
+    let mut received = Vec::new();
+    while let Some(event) = event_receiver.recv().await {
+        received.push(event);
+    }
+    assert_eq!(received, vec!["joined", "close"]);
+}
@@ -0,0 +1,155 @@
+use std::convert::TryFrom;
+use std::io::Write;
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use xml::reader::{EventReader, XmlEvent as XmlReadEvent};
+use xml::writer::EmitterConfig;
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::multi_client::{JoinTarget, MultiPluginClient};
+use socha_client_base::plugin::{HasRules, SCPlugin};
+use socha_client_base::protocol::{Data, Joined, Room};
+use socha_client_base::transport::{MemoryTransport, Transport};
+use socha_client_base::xml_node::{FromXmlNode, XmlNode};
+use socha_plugin_2020::game as game_2020;
+use socha_plugin_2020::plugin::SCPlugin2020;
+use socha_plugin_2021::game as game_2021;
+use socha_plugin_2021::plugin::SCPlugin2021;
+use socha_server::plugin::SCServerPlugin;
+use socha_server::room::Timeouts;
+use socha_server::server::SCServer;
+
+/// A Hive delegate that reports its game
+/// type and plays the first possible move.
+struct HiveDelegate {
+    games: Sender<&'static str>
+}
+
+impl SCClientDelegate for HiveDelegate {
+    type Plugin = SCPlugin2020;
+
+    fn request_move(&mut self, state: &game_2020::GameState, _my_color: game_2020::PlayerColor, _history: &GameHistory<SCPlugin2020>, _budget: &MoveBudget<game_2020::Move>) -> game_2020::Move {
+        self.games.send(SCPlugin2020::protocol_game_type()).unwrap();
+        HasRules::possible_moves(state).into_iter().next().expect("No move available")
+    }
+}
+
+/// A Blokus delegate that reports its game type,
+/// places the start piece and skips afterwards.
+struct BlokusDelegate {
+    games: Sender<&'static str>
+}
+
+impl SCClientDelegate for BlokusDelegate {
+    type Plugin = SCPlugin2021;
+
+    fn request_move(&mut self, state: &game_2021::GameState, _my_team: game_2021::Team, _history: &GameHistory<SCPlugin2021>, _budget: &MoveBudget<game_2021::Move>) -> game_2021::Move {
+        self.games.send(SCPlugin2021::protocol_game_type()).unwrap();
+        if state.is_first_move() {
+            state.possible_moves().next().expect("No start move available")
+        } else {
+            game_2021::Move::Skip { color: state.current_color() }
+        }
+    }
+}
+
+fn multi_client(games: Sender<&'static str>) -> MultiPluginClient {
+    MultiPluginClient::new()
+        .with_client(SCClient::new(HiveDelegate { games: games.clone() }, DebugMode::default()))
+        .with_client(SCClient::new(BlokusDelegate { games }, DebugMode::default()))
+}
+
+/// Acts as a server sending the given join response and
+/// a move request, and returns the move sent by the client.
+fn request_move<P>(transport: MemoryTransport, joined: Joined, state: P::GameState, color: P::PlayerColor) -> P::Move where P: SCPlugin {
+    let (reader, writer) = transport.split().unwrap();
+    let mut xml_reader = EventReader::new(reader);
+    let mut emitter_config = EmitterConfig::new();
+    emitter_config.write_document_declaration = false;
+    let mut xml_writer = emitter_config.create_writer(writer);
+
+    loop {
+        match xml_reader.next().unwrap() {
+            XmlReadEvent::StartElement { name, .. } if name.local_name == "protocol" => break,
+            _ => ()
+        }
+    }
+    let join = XmlNode::read_from(&mut xml_reader).unwrap();
+    assert_eq!(join.name(), "joinPrepared");
+
+    xml_writer.inner_mut().write_all("<protocol>".as_bytes()).unwrap();
+    XmlNode::from(joined).write_to(&mut xml_writer).unwrap();
+    for data in [Data::<P>::WelcomeMessage { color }, Data::Memento { state }, Data::MoveRequest] {
+        XmlNode::try_from(Room::<P> { room_id: "test".to_owned(), data }).unwrap().write_to(&mut xml_writer).unwrap();
+    }
+
+    let room = <Room<P>>::from_node(&XmlNode::read_from(&mut xml_reader).unwrap()).unwrap();
+    XmlNode::new("sc.protocol.responses.CloseConnection").build().write_to(&mut xml_writer).unwrap();
+    match room.data {
+        Data::Move(game_move) => game_move,
+        data => panic!("Expected a move, got {:?}", data)
+    }
+}
+
+#[test]
+fn test_selecting_plugin_from_messages() {
+    let (games, game_receiver) = channel();
+    let mut client = multi_client(games);
+    assert_eq!(client.game_types(), vec!["swc_2020_hive", "swc_2021_blokus"]);
+
+    // Without a game type in the join response, the plugin is
+    // chosen by the first room message, which is read again
+    let (client_end, server_end) = MemoryTransport::pair();
+    let server = thread::spawn(move || {
+        let state = SCPlugin2020::initial_state();
+        let joined = Joined { room_id: "test".to_owned(), game_type: String::new() };
+        let game_move = request_move::<SCPlugin2020>(server_end, joined, state.clone(), game_2020::PlayerColor::Red);
+        assert!(HasRules::validate_move(&state, &game_move).is_ok());
+    });
+    client.run_with(client_end, JoinTarget::Reservation("hive")).unwrap();
+    server.join().unwrap();
+
+    let (client_end, server_end) = MemoryTransport::pair();
+    let server = thread::spawn(move || {
+        let state = SCPlugin2021::initial_state();
+        let joined = Joined { room_id: "test".to_owned(), game_type: "swc_2021_blokus".to_owned() };
+        let game_move = request_move::<SCPlugin2021>(server_end, joined, state.clone(), game_2021::Team::One);
+        assert!(state.validate_move(&game_move).is_ok());
+    });
+    client.run_with(client_end, JoinTarget::Reservation("blokus")).unwrap();
+    server.join().unwrap();
+
+    assert_eq!(game_receiver.try_iter().collect::<Vec<_>>(), vec!["swc_2020_hive", "swc_2021_blokus"]);
+}
+
+#[test]
+fn test_unsupported_game_type() {
+    let (games, _game_receiver) = channel();
+    let mut client = multi_client(games);
+    let (client_end, _server_end) = MemoryTransport::pair();
+    assert!(client.run_with(client_end, JoinTarget::GameType("swc_2019_piranhas")).is_err());
+}
+
+#[test]
+fn test_prepared_game() {
+    let server = SCServer::<SCPlugin2021>::bind("127.0.0.1:0", Timeouts::default()).unwrap();
+    let port = server.local_addr().unwrap().port();
+    let reservations = server.prepare_game();
+    thread::spawn(move || server.run().unwrap());
+
+    let clients: Vec<_> = reservations.into_iter().map(|reservation| {
+        thread::spawn(move || {
+            let (games, _game_receiver) = channel();
+            multi_client(games).run("127.0.0.1", port, JoinTarget::Reservation(&reservation)).unwrap()
+        })
+    }).collect();
+
+    for client in clients {
+        let summaries = client.join().unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].game_type, "swc_2021_blokus");
+        assert!(summaries[0].my_score.is_some());
+        assert!(!summaries[0].turns.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::{BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use std::time::Duration;
+use xml::reader::{EventReader, XmlEvent as XmlReadEvent};
+use socha_client_base::client::{DebugMode, SCClient, SCClientDelegate};
+use socha_client_base::history::GameHistory;
+use socha_client_base::move_budget::MoveBudget;
+use socha_client_base::protocol::{GameResult, ScoreCause};
+use socha_client_base::xml_node::XmlNode;
+use socha_plugin_2021::game::{GameState, Move, Team};
+use socha_plugin_2021::plugin::SCPlugin2021;
+use socha_server::room::Timeouts;
+use socha_server::server::SCServer;
+
+/// Short server timeouts, which are still far
+/// longer than the test delegates need.
+const TIMEOUTS: Timeouts = Timeouts { soft: Some(Duration::from_secs(1)), hard: Some(Duration::from_secs(2)) };
+
+/// A delegate that places the start pieces and
+/// skips afterwards, or always skips if illegal.
+struct SkippingDelegate {
+    illegal: bool,
+    results: Sender<GameResult<SCPlugin2021>>
+}
+
+impl SCClientDelegate for SkippingDelegate {
+    type Plugin = SCPlugin2021;
+
+    fn on_game_end(&mut self, result: GameResult<SCPlugin2021>) {
+        self.results.send(result).unwrap();
+    }
+
+    fn request_move(&mut self, state: &GameState, _my_team: Team, _history: &GameHistory<SCPlugin2021>, _budget: &MoveBudget<Move>) -> Move {
+        if state.is_first_move() && !self.illegal {
+            state.possible_moves().next().expect("No start move available")
+        } else {
+            Move::Skip { color: state.current_color() }
+        }
+    }
+}
+
+/// Starts a server, returning its port and the
+/// reservations for both players, if prepared.
+fn start_server(prepared: bool) -> (u16, Vec<Option<String>>) {
+    let server = SCServer::<SCPlugin2021>::bind("127.0.0.1:0", TIMEOUTS).unwrap();
+    let port = server.local_addr().unwrap().port();
+    let reservations = if prepared {
+        server.prepare_game().into_iter().map(Some).collect()
+    } else {
+        vec![None, None]
+    };
+    thread::spawn(move || server.run().unwrap());
+    (port, reservations)
+}
+
+/// Plays a game between two clients on the given
+/// server and returns the results they received.
+fn play_game(port: u16, reservations: Vec<Option<String>>, illegal: bool) -> Vec<GameResult<SCPlugin2021>> {
+    let (results, receiver) = channel();
+    let clients: Vec<_> = reservations.into_iter().map(|reservation| {
+        let client = SCClient::new(SkippingDelegate { illegal, results: results.clone() }, DebugMode::default());
+        thread::spawn(move || client.run("127.0.0.1", port, reservation.as_deref()).unwrap())
+    }).collect();
+
+    for client in clients {
+        assert_eq!(client.join().unwrap().len(), 1);
+    }
+    receiver.try_iter().collect()
+}
+
+#[test]
+fn test_joined_game() {
+    let (port, reservations) = start_server(false);
+    let results = play_game(port, reservations, false);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0], results[1]);
+
+    let result = &results[0];
+    assert_eq!(result.scores.len(), 2);
+    assert!(result.scores.iter().all(|s| s.cause == ScoreCause::Regular));
+    assert_eq!(result.definition.fragments.len(), 2);
+    assert!(result.scores.iter().all(|s| s.parts.len() == 2));
+}
+
+#[test]
+fn test_prepared_game() {
+    let (port, reservations) = start_server(true);
+    assert_eq!(play_game(port, reservations, false).len(), 2);
+}
+
+#[test]
+fn test_rule_violation() {
+    let (port, reservations) = start_server(false);
+    let results = play_game(port, reservations, true);
+    assert_eq!(results.len(), 2);
+    assert!(results[0].scores.iter().any(|s| s.cause == ScoreCause::RuleViolation));
+}
+
+#[test]
+fn test_disconnected_waiting_player() {
+    let (port, reservations) = start_server(false);
+
+    // Joins and disconnects once the server has placed the client in a room
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    stream.write_all(r#"<protocol><join gameType="swc_2021_blokus" />"#.as_bytes()).unwrap();
+    let mut xml_reader = EventReader::new(BufReader::new(stream));
+    loop {
+        match xml_reader.next().unwrap() {
+            XmlReadEvent::StartElement { name, .. } if name.local_name == "protocol" => break,
+            _ => ()
+        }
+    }
+    assert_eq!(XmlNode::read_from(&mut xml_reader).unwrap().name(), "joined");
+    drop(xml_reader);
+
+    // The next clients are paired with each other instead
+    let results = play_game(port, reservations, false);
+    assert_eq!(results.len(), 2);
+    assert!(results[0].scores.iter().all(|s| s.cause == ScoreCause::Regular));
+}
@@ -0,0 +1,261 @@
+//! Derive macros for converting structs from and into
+//! `XmlNode`s of the Software Challenge protocol.
+//!
+//! Every named field is mapped to one part of the node,
+//! which is selected using the `#[xml(...)]` attribute:
+//!
+//! - `attribute` (the default) parses an attribute
+//! - `content` parses the node's textual contents
+//! - `child` converts the child element
+//! - `childs` converts all repeated child elements into a `Vec`
+//! - `child_content` parses the textual contents of a child
+//! - `childs_content` parses the contents of repeated childs into a `Vec`
+//!
+//! Names are derived from the identifiers in camel case
+//! and can be overridden using `rename = "..."`. Missing
+//! attributes can fall back to `Default` using `default`.
+//! On the struct itself, `name = "..."` sets the tag name
+//! and `class = "..."` the expected `class` attribute.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, Ident, LitStr, Result};
+
+/// Derives `FromXmlNode` for a struct with named fields.
+#[proc_macro_derive(FromXmlNode, attributes(xml))]
+pub fn derive_from_xml_node(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_xml_node(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Derives `From<...> for XmlNode` for a struct with named fields.
+#[proc_macro_derive(IntoXmlNode, attributes(xml))]
+pub fn derive_into_xml_node(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_into_xml_node(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// The part of a node a field is mapped to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Attribute,
+    Content,
+    Child,
+    Childs,
+    ChildContent,
+    ChildsContent
+}
+
+/// The options of a field.
+struct XmlField {
+    ident: Ident,
+    kind: FieldKind,
+    name: String,
+    default: bool
+}
+
+/// The options of the struct itself.
+struct XmlStruct {
+    name: String,
+    class: Option<String>,
+    fields: Vec<XmlField>
+}
+
+fn expand_from_xml_node(input: &DeriveInput) -> Result<TokenStream2> {
+    let parsed = parse_struct(input)?;
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let class_check = parsed.class.as_ref().map(|class| quote! {
+        let class = node.attribute("class")?;
+        if class != #class {
+            return Err(node.unknown_class(class));
+        }
+    });
+    let fields = parsed.fields.iter().map(|field| {
+        let ident = &field.ident;
+        let name = &field.name;
+        let value = match field.kind {
+            FieldKind::Attribute if field.default => quote! {
+                node.attribute(#name).map_or(Ok(::std::default::Default::default()), |_| node.parse_attribute(#name))?
+            },
+            FieldKind::Attribute => quote! { node.parse_attribute(#name)? },
+            FieldKind::Content => quote! { node.parse_content()? },
+            FieldKind::Child => quote! {
+                ::socha_client_base::xml_node::FromXmlNode::from_node(node.child_by_name(#name)?)?
+            },
+            FieldKind::Childs => quote! {
+                node.childs_by_name(#name)
+                    .map(::socha_client_base::xml_node::FromXmlNode::from_node)
+                    .collect::<::socha_client_base::util::SCResult<_>>()?
+            },
+            FieldKind::ChildContent => quote! { node.child_by_name(#name)?.parse_content()? },
+            FieldKind::ChildsContent => quote! {
+                node.childs_by_name(#name)
+                    .map(|child| child.parse_content())
+                    .collect::<::socha_client_base::util::SCResult<_>>()?
+            }
+        };
+        quote! { #ident: #value }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::socha_client_base::xml_node::FromXmlNode for #ident #type_generics #where_clause {
+            fn from_node(node: &::socha_client_base::xml_node::XmlNode) -> ::socha_client_base::util::SCResult<Self> {
+                #class_check
+                Ok(Self {
+                    #(#fields),*
+                })
+            }
+        }
+    })
+}
+
+fn expand_into_xml_node(input: &DeriveInput) -> Result<TokenStream2> {
+    let parsed = parse_struct(input)?;
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let name = &parsed.name;
+
+    let class = parsed.class.as_ref().map(|class| quote! { .attribute("class", #class) });
+    let fields = parsed.fields.iter().map(|field| {
+        let ident = &field.ident;
+        let name = &field.name;
+        match field.kind {
+            FieldKind::Attribute => quote! {
+                .attribute(#name, ::std::string::ToString::to_string(&value.#ident))
+            },
+            FieldKind::Content => quote! {
+                .content(&::std::string::ToString::to_string(&value.#ident))
+            },
+            FieldKind::Child => quote! {
+                .child(::socha_client_base::xml_node::XmlNode::from(value.#ident).renamed(#name))
+            },
+            FieldKind::Childs => quote! {
+                .childs(value.#ident.into_iter().map(|child| ::socha_client_base::xml_node::XmlNode::from(child).renamed(#name)))
+            },
+            FieldKind::ChildContent => quote! {
+                .child(::socha_client_base::xml_node::XmlNode::new(#name).content(&::std::string::ToString::to_string(&value.#ident)))
+            },
+            FieldKind::ChildsContent => quote! {
+                .childs(value.#ident.iter().map(|child| ::socha_client_base::xml_node::XmlNode::new(#name).content(&::std::string::ToString::to_string(child)).build()))
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::std::convert::From<#ident #type_generics> for ::socha_client_base::xml_node::XmlNode #where_clause {
+            fn from(value: #ident #type_generics) -> Self {
+                ::socha_client_base::xml_node::XmlNode::new(#name)
+                    #class
+                    #(#fields)*
+                    .build()
+            }
+        }
+    })
+}
+
+fn parse_struct(input: &DeriveInput) -> Result<XmlStruct> {
+    let named = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref named) => &named.named,
+            Fields::Unit => return parse_struct_options(input, Vec::new()),
+            Fields::Unnamed(_) => return Err(Error::new_spanned(&input.ident, "XML nodes can only be derived for structs with named fields"))
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "XML nodes can only be derived for structs"))
+    };
+
+    let mut fields = Vec::new();
+    for field in named {
+        let ident = field.ident.clone().expect("Named fields have identifiers");
+        let mut kind = FieldKind::Attribute;
+        let mut name = None;
+        let mut default = false;
+
+        for_each_option(&field.attrs, |option| {
+            if option.path.is_ident("attribute") {
+                kind = FieldKind::Attribute;
+            } else if option.path.is_ident("content") {
+                kind = FieldKind::Content;
+            } else if option.path.is_ident("child") {
+                kind = FieldKind::Child;
+            } else if option.path.is_ident("childs") {
+                kind = FieldKind::Childs;
+            } else if option.path.is_ident("child_content") {
+                kind = FieldKind::ChildContent;
+            } else if option.path.is_ident("childs_content") {
+                kind = FieldKind::ChildsContent;
+            } else if option.path.is_ident("default") {
+                default = true;
+            } else if option.path.is_ident("rename") {
+                name = Some(option.value()?.parse::<LitStr>()?.value());
+            } else {
+                return Err(option.error("Unrecognized field option"));
+            }
+            Ok(())
+        })?;
+
+        if default && kind != FieldKind::Attribute {
+            return Err(Error::new_spanned(&ident, "Only attributes can have a default"));
+        }
+        let name = name.unwrap_or_else(|| camel_case(&ident.to_string()));
+        fields.push(XmlField { ident, kind, name, default });
+    }
+
+    if fields.iter().filter(|f| f.kind == FieldKind::Content).count() > 1 {
+        return Err(Error::new(Span::call_site(), "At most one field can be mapped to the content"));
+    }
+
+    parse_struct_options(input, fields)
+}
+
+fn parse_struct_options(input: &DeriveInput, fields: Vec<XmlField>) -> Result<XmlStruct> {
+    let mut name = None;
+    let mut class = None;
+
+    for_each_option(&input.attrs, |option| {
+        if option.path.is_ident("name") {
+            name = Some(option.value()?.parse::<LitStr>()?.value());
+        } else if option.path.is_ident("class") {
+            class = Some(option.value()?.parse::<LitStr>()?.value());
+        } else {
+            return Err(option.error("Unrecognized struct option"));
+        }
+        Ok(())
+    })?;
+
+    let name = name.unwrap_or_else(|| camel_case(&input.ident.to_string()));
+    Ok(XmlStruct { name, class, fields })
+}
+
+/// Invokes the callback for every option
+/// found in `#[xml(...)]` attributes.
+fn for_each_option(attrs: &[Attribute], mut callback: impl FnMut(syn::meta::ParseNestedMeta) -> Result<()>) -> Result<()> {
+    for attr in attrs.iter().filter(|a| a.path().is_ident("xml")) {
+        attr.parse_nested_meta(&mut callback)?;
+    }
+    Ok(())
+}
+
+/// Converts a snake or pascal case identifier to
+/// the camel case used by the protocol.
+fn camel_case(ident: &str) -> String {
+    let mut result = String::new();
+    let mut upper = false;
+    for c in ident.trim_start_matches("r#").chars() {
+        if c == '_' {
+            upper = !result.is_empty();
+        } else if result.is_empty() {
+            result.extend(c.to_lowercase());
+        } else if upper {
+            result.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}